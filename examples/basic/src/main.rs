@@ -119,6 +119,7 @@ impl Example {
                         self.nodes[*to_index].position.y + 37.5,
                     ),
                 )
+                .arrowhead(true)
                 .into(),
             );
         }
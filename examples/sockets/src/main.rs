@@ -1,8 +1,8 @@
 use iced::widget::{button, container, text};
-use iced::{Color, Element, Length, Padding, Point};
+use iced::{Color, Element, Length, Padding, Point, Size};
 use iced_node_editor::{
-    graph_container, node, Connection, Endpoint, GraphNodeElement, Link, LogicalEndpoint, Matrix,
-    Socket, SocketRole, SocketSide,
+    graph_container, node, reroute, Connection, ConnectionStyle, Endpoint, Flow, GraphNodeElement,
+    Link, LogicalEndpoint, Matrix, Socket, SocketRole, SocketShape, SocketSide,
 };
 use std::collections::HashMap;
 
@@ -39,13 +39,16 @@ struct NodeState {
     position: Point,
     text: String,
     button: bool,
+    collapsed: bool,
+    size: Size,
     sockets: (Vec<SocketType>, Vec<SocketType>),
 }
 
 // Define some types that sockets may have.
-// The library does not perform any sort of type checking; it is entirely up to user code to verify
-// that sockets with correct types are connected to each other. In this example, we just use the
-// types to provide sockets with two different appearances (that behave identically).
+// The library does not know about these types; instead, it asks user code whether two sockets may
+// be connected via the `can_connect` predicate. In this example, only sockets of the same type
+// may be connected, and the types also give sockets two different appearances.
+#[derive(PartialEq, Eq)]
 enum SocketType {
     BlueSquare,
     RedCircle,
@@ -67,6 +70,13 @@ struct Example {
     // in the value type.
     connections: HashMap<(usize, usize), (usize, usize)>,
 
+    // The points that connections pass through, keyed like `connections`. Users can add, move and
+    // remove them by (double-)clicking and dragging.
+    waypoints: HashMap<(usize, usize), Vec<Point>>,
+
+    // The positions of reroutes, which users can insert by double-clicking a connection
+    reroutes: Vec<Point>,
+
     // Our own representation of the “dangling connection” — the connection that follows the user's
     // mouse pointer in the process of connecting two sockets with each other.
     // It is divided into two parts:
@@ -83,14 +93,43 @@ enum Message {
     ScaleChanged(f32, f32, f32),
     TranslationChanged(f32, f32),
     MoveNode(usize, f32, f32),
+    ToggleCollapse(usize, bool),
+    ResizeNode(usize, Size),
+    MoveReroute(usize, f32, f32),
     Connect(Link),
     Disconnect(LogicalEndpoint, Point),
     Dangling(Option<(LogicalEndpoint, Link)>),
+    WaypointsChanged(Link, Vec<Point>),
+    Reroute(Link, Point),
     ButtonPressed,
 }
 
 impl Example {
 
+    fn socket_type(&self, endpoint: &LogicalEndpoint) -> Option<&SocketType> {
+        // Reroutes take on the type of the connection passing through them
+        if self.is_reroute(endpoint.node_index) {
+            let (start_node_index, start_socket_index) =
+                self.connections.get(&(endpoint.node_index, 0))?;
+            return self.socket_type(&LogicalEndpoint {
+                node_index: *start_node_index,
+                role: SocketRole::Out,
+                socket_index: *start_socket_index,
+            });
+        }
+
+        let (in_sockets, out_sockets) = &self.nodes.get(endpoint.node_index)?.sockets;
+        match endpoint.role {
+            SocketRole::In => in_sockets.get(endpoint.socket_index),
+            SocketRole::Out => out_sockets.get(endpoint.socket_index),
+        }
+    }
+
+    // Reroutes come after the nodes in the graph content, so their node indices do, too
+    fn is_reroute(&self, node_index: usize) -> bool {
+        node_index >= self.nodes.len()
+    }
+
     fn new() -> Self {
         let mut connections = HashMap::new();
         connections.insert((2, 0), (1, 1)); // Output socket #1 of node #1 to input socket #0 of node #2
@@ -104,6 +143,8 @@ impl Example {
                     position: Point::new(0.0, 0.0),
                     text: String::from("Iced"),
                     button: false,
+                    collapsed: false,
+                    size: Size::new(200.0, 75.0),
                     sockets: (vec![], vec![SocketType::BlueSquare, SocketType::RedCircle]),
                 },
                 // Node #1
@@ -111,6 +152,8 @@ impl Example {
                     position: Point::new(250.0, 250.0),
                     text: String::from("Node"),
                     button: false,
+                    collapsed: false,
+                    size: Size::new(200.0, 75.0),
                     sockets: (
                         vec![SocketType::RedCircle],
                         vec![
@@ -125,10 +168,14 @@ impl Example {
                     position: Point::new(500.0, 250.0),
                     text: String::from("Editor"),
                     button: true,
+                    collapsed: false,
+                    size: Size::new(200.0, 75.0),
                     sockets: (vec![SocketType::BlueSquare, SocketType::RedCircle], vec![]),
                 },
             ],
             connections,
+            waypoints: HashMap::new(),
+            reroutes: vec![],
             dangling_source: None,
             dangling_connection: None,
         }
@@ -150,6 +197,14 @@ impl Example {
                     self.nodes[i].position.y + y / self.matrix.get_scale(),
                 );
             }
+            Message::ToggleCollapse(i, collapsed) => self.nodes[i].collapsed = collapsed,
+            Message::ResizeNode(i, size) => self.nodes[i].size = size,
+            Message::MoveReroute(i, x, y) => {
+                self.reroutes[i] = Point::new(
+                    self.reroutes[i].x + x / self.matrix.get_scale(),
+                    self.reroutes[i].y + y / self.matrix.get_scale(),
+                );
+            }
             Message::Connect(link) => {
                 // The call to `unwrap_sockets` will panic if the `link` contains absolute
                 // endpoints. But the `Connect` message is guaranteed to only contain `Link`s with
@@ -158,6 +213,7 @@ impl Example {
 
                 // Insert the new connection. The hash map design ensures that this will delete any
                 // potentially previously present connections ending in the same node.
+                self.waypoints.remove(&(end.node_index, end.socket_index));
                 self.connections.insert(
                     (end.node_index, end.socket_index),
                     (start.node_index, start.socket_index),
@@ -165,6 +221,7 @@ impl Example {
             }
             Message::Disconnect(endpoint, new_dangling_end_position) => {
                 // Remove the connection that ends in the socket, if it exists
+                self.waypoints.remove(&(endpoint.node_index, endpoint.socket_index));
                 if let Some((start_node_index, start_socket_index)) = self
                     .connections
                     .remove(&(endpoint.node_index, endpoint.socket_index))
@@ -205,6 +262,24 @@ impl Example {
                 self.dangling_source = None;
                 self.dangling_connection = None;
             }
+            Message::WaypointsChanged(link, waypoints) => {
+                // Only connections between two sockets are editable; the dangling connection is
+                // gone by the time waypoints could be edited
+                let (_, end) = link.unwrap_sockets();
+                self.waypoints.insert((end.node_index, end.socket_index), waypoints);
+            }
+            Message::Reroute(link, position) => {
+                // Split the connection in two, passing through a new reroute
+                let (start, end) = link.unwrap_sockets();
+                let reroute_index = self.nodes.len() + self.reroutes.len();
+                self.reroutes.push(position);
+
+                self.waypoints.remove(&(end.node_index, end.socket_index));
+                self.connections
+                    .insert((end.node_index, end.socket_index), (reroute_index, 0));
+                self.connections
+                    .insert((reroute_index, 0), (start.node_index, start.socket_index));
+            }
             Message::ButtonPressed => println!("Button was pressed."),
         }
     }
@@ -226,10 +301,11 @@ impl Example {
             }
 
             let node = if n.button {
-                node(iced::widget::column![
-                    text(&n.text),
-                    button("Button").on_press(Message::ButtonPressed)
-                ])
+                node(button("Button").on_press(Message::ButtonPressed))
+                    .header(text(&n.text))
+                    .drag_by_header(true)
+                    .collapsed(n.collapsed)
+                    .on_toggle_collapse(move |collapsed| Message::ToggleCollapse(i, collapsed))
             } else {
                 node(text(&n.text))
             };
@@ -240,13 +316,22 @@ impl Example {
                     .center_x()
                     .center_y()
                     .on_translate(move |p| Message::MoveNode(i, p.0, p.1))
-                    .width(Length::Fixed(200.0))
-                    .height(Length::Fixed(75.0))
+                    .on_resize(move |size| Message::ResizeNode(i, size))
+                    .width(Length::Fixed(n.size.width))
+                    .height(Length::Fixed(n.size.height))
                     .position(n.position)
                     .into(),
             );
         }
 
+        for (i, position) in self.reroutes.iter().enumerate() {
+            graph_content.push(
+                reroute(*position)
+                    .on_translate(move |p| Message::MoveReroute(i, p.0, p.1))
+                    .into(),
+            );
+        }
+
         // Convert our own `HashMap` representation of connections into the one used by the library.
         // Here it is important that this happens *after* the nodes have been added.
         // The socket layouting logic needs to process first the nodes, then the connections,
@@ -254,36 +339,71 @@ impl Example {
         for ((end_node_index, end_socket_index), (start_node_index, start_socket_index)) in
             self.connections.iter()
         {
+            let start = LogicalEndpoint {
+                node_index: *start_node_index,
+                role: SocketRole::Out,
+                socket_index: *start_socket_index,
+            };
+
+            // Pretend that data is flowing through connections between red circles, and animate
+            // them with dots travelling along
+            let flow = (self.socket_type(&start) == Some(&SocketType::RedCircle)).then_some(Flow {
+                dots: true,
+                ..Flow::default()
+            });
+
             graph_content.push(
                 Connection::between(
-                    Endpoint::Socket(LogicalEndpoint {
-                        node_index: *start_node_index,
-                        role: SocketRole::Out,
-                        socket_index: *start_socket_index,
-                    }),
+                    Endpoint::Socket(start),
                     Endpoint::Socket(LogicalEndpoint {
                         node_index: *end_node_index,
                         role: SocketRole::In,
                         socket_index: *end_socket_index,
                     }),
                 )
+                .path_style(ConnectionStyle::Bezier)
+                .flow(flow)
+                .waypoints(
+                    self.waypoints
+                        .get(&(*end_node_index, *end_socket_index))
+                        .cloned()
+                        .unwrap_or_default(),
+                )
                 .into(),
             );
         }
 
         // Append the dangling connection, if one exists
         if let Some(link) = &self.dangling_connection {
-            graph_content.push(Connection::new(link.clone()).into())
+            graph_content.push(
+                Connection::new(link.clone())
+                    .path_style(ConnectionStyle::Bezier)
+                    .flow(Some(Flow::default()))
+                    .into(),
+            )
         }
 
         container(
             graph_container(graph_content)
                 .dangling_source(self.dangling_source)
+                .can_connect(|start, end| {
+                    // Only allow connections between sockets of the same type, except for
+                    // reroutes that nothing is connected to yet
+                    match (self.socket_type(start), self.socket_type(end)) {
+                        (Some(start_type), Some(end_type)) => start_type == end_type,
+                        _ => self.is_reroute(start.node_index) || self.is_reroute(end.node_index),
+                    }
+                })
+                .prevent_cycles(true)
+                .snap_radius(15.0)
+                .parallel_spacing(12.0)
                 .on_translate(|p| Message::TranslationChanged(p.0, p.1))
                 .on_scale(Message::ScaleChanged)
                 .on_connect(Message::Connect)
                 .on_disconnect(Message::Disconnect)
                 .on_dangling(Message::Dangling)
+                .on_waypoints_changed(Message::WaypointsChanged)
+                .on_reroute(Message::Reroute)
                 .width(Length::Fill)
                 .height(Length::Fill)
                 .matrix(self.matrix),
@@ -326,19 +446,19 @@ fn make_socket<'a>(
 
     // The style of the blob is not determined by a style sheet, but by properties of the `Socket`
     // itself.
-    let (blob_border_radius, blob_color, content) = match socket_type {
+    let (blob_shape, blob_color, content) = match socket_type {
         SocketType::BlueSquare => (
-            0.0,
+            SocketShape::Square,
             Color::from_rgb(0.0, 0.1, 0.8),
             text("Blue square").into(),
         ),
         SocketType::RedCircle => (
-            BLOB_RADIUS,
+            SocketShape::Circle,
             Color::from_rgb(0.8, 0.1, 0.0),
             text("Red circle").into(),
         ),
         SocketType::Button => (
-            BLOB_RADIUS,
+            SocketShape::Triangle,
             Color::from_rgb(0.3, 0.3, 0.3),
             button("Button").on_press(Message::ButtonPressed).into(),
         ),
@@ -350,12 +470,24 @@ fn make_socket<'a>(
         content_alignment,
 
         blob_radius: BLOB_RADIUS,
-        blob_border_radius,
+        blob_shape,
+        blob_border_radius: 0.0, // Only used by `SocketShape::Rounded`
         blob_color,
         content, // Arbitrary widgets can be used here.
 
         min_height: 0.0,
         max_height: f32::INFINITY,
         blob_border_color: None, // If `None`, the one from the style sheet will be used.
+
+        // Shown after hovering over the blob for a moment; arbitrary widgets can be used here, too
+        tooltip: match socket_type {
+            SocketType::Button => None,
+            _ => Some(
+                container(text("Connects only to sockets of the same type").size(12))
+                    .padding(5)
+                    .style(container::rounded_box)
+                    .into(),
+            ),
+        },
     }
 }
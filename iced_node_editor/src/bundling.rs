@@ -0,0 +1,287 @@
+use iced::Vector;
+
+/// The number of cycles of the simulation. Every cycle after the first doubles the number of
+/// segments per path, starting at two.
+const CYCLES: usize = 6;
+
+/// The number of iterations in the first cycle, which is reduced by `ITERATION_RATE` per cycle.
+const INITIAL_ITERATIONS: f32 = 90.0;
+const ITERATION_RATE: f32 = 2.0 / 3.0;
+
+/// The distance (in graph units) points move per unit of force in the first cycle, halved with
+/// every cycle.
+const INITIAL_STEP: f32 = 0.1;
+
+/// The stiffness of the springs that keep the points of a path together.
+const SPRING_CONSTANT: f32 = 0.1;
+
+/// The compatibility two paths need to have in order to attract each other, from 0 to 1.
+const COMPATIBILITY_THRESHOLD: f32 = 0.6;
+
+/// The number of passes smoothing the bundled paths, which would be jagged otherwise.
+const SMOOTHING_PASSES: usize = 2;
+
+/// Bundles paths with force-directed edge bundling (Holten and van Wijk, 2009): the paths are
+/// subdivided into points which attract the corresponding points of compatible paths, i.e. of
+/// paths that have a similar direction, length and position. Only the end points of the paths
+/// are considered when checking compatibility, and stay in place.
+pub(crate) fn bundle_edges(paths: &[Vec<Vector>]) -> Vec<Vec<Vector>> {
+    let ends: Vec<_> = paths
+        .iter()
+        .map(|path| (path[0], path[path.len() - 1]))
+        .collect();
+    // For every path, the compatible paths and whether they run in the opposite direction
+    let compatible: Vec<Vec<(usize, bool)>> = (0..ends.len())
+        .map(|i| {
+            (0..ends.len())
+                .filter(|&j| i != j && compatibility(ends[i], ends[j]) >= COMPATIBILITY_THRESHOLD)
+                .map(|j| {
+                    let reversed = dot(ends[i].1 - ends[i].0, ends[j].1 - ends[j].0) < 0.0;
+                    (j, reversed)
+                })
+                .collect()
+        })
+        .collect();
+
+    // Paths start out with a single point between their ends, which is the only one that moves
+    let mut subdivisions = 2;
+    let mut paths: Vec<Vec<Vector>> = paths
+        .iter()
+        .map(|path| resample(path, subdivisions))
+        .collect();
+    let mut step = INITIAL_STEP;
+    let mut iterations = INITIAL_ITERATIONS;
+
+    for cycle in 0..CYCLES {
+        if cycle > 0 {
+            subdivisions *= 2;
+            paths = paths
+                .iter()
+                .map(|path| resample(path, subdivisions))
+                .collect();
+            step /= 2.0;
+            iterations *= ITERATION_RATE;
+        }
+
+        for _ in 0..iterations.round() as usize {
+            let forces: Vec<Vec<Vector>> = (0..paths.len())
+                .map(|i| forces(&paths, i, &compatible[i], ends[i]))
+                .collect();
+            for (path, forces) in paths.iter_mut().zip(forces) {
+                for (point, force) in path.iter_mut().zip(forces) {
+                    *point = *point + force * step;
+                }
+            }
+        }
+    }
+
+    for path in &mut paths {
+        for _ in 0..SMOOTHING_PASSES {
+            *path = smooth(path);
+        }
+    }
+
+    paths
+}
+
+/// Computes the forces on the points of the path with the given index. The end points never move.
+fn forces(
+    paths: &[Vec<Vector>],
+    index: usize,
+    compatible: &[(usize, bool)],
+    (start, end): (Vector, Vector),
+) -> Vec<Vector> {
+    let path = &paths[index];
+    let subdivisions = path.len() - 1;
+    let spring = SPRING_CONSTANT / (length(end - start).max(1.0) * subdivisions as f32);
+
+    let mut forces = vec![Vector::new(0.0, 0.0); path.len()];
+    for i in 1..subdivisions {
+        let point = path[i];
+        let mut force = ((path[i - 1] - point) + (path[i + 1] - point)) * spring;
+
+        for &(other, reversed) in compatible {
+            let other_point = if reversed {
+                paths[other][subdivisions - i]
+            } else {
+                paths[other][i]
+            };
+            let delta = other_point - point;
+            let distance = length(delta);
+            if distance > f32::EPSILON {
+                force = force + delta * (1.0 / distance);
+            }
+        }
+        forces[i] = force;
+    }
+
+    forces
+}
+
+/// Returns how likely two paths, given by their end points, are to be bundled, from 0 to 1.
+fn compatibility(p: (Vector, Vector), q: (Vector, Vector)) -> f32 {
+    let p_vector = p.1 - p.0;
+    let q_vector = q.1 - q.0;
+    let p_length = length(p_vector);
+    let q_length = length(q_vector);
+    if p_length < f32::EPSILON || q_length < f32::EPSILON {
+        return 0.0;
+    }
+
+    let angle = (dot(p_vector, q_vector) / (p_length * q_length)).abs();
+
+    let average = (p_length + q_length) / 2.0;
+    let scale = 2.0 / (average / p_length.min(q_length) + p_length.max(q_length) / average);
+
+    let p_middle = (p.0 + p.1) * 0.5;
+    let q_middle = (q.0 + q.1) * 0.5;
+    let position = average / (average + length(p_middle - q_middle));
+
+    let visibility = visibility(p, q).min(visibility(q, p));
+
+    angle * scale * position * visibility
+}
+
+/// Returns how much of `p`, projected onto the line through `q`, overlaps `q` around its middle.
+fn visibility(p: (Vector, Vector), q: (Vector, Vector)) -> f32 {
+    let start = project(p.0, q);
+    let end = project(p.1, q);
+    let projected = length(end - start);
+    if projected < f32::EPSILON {
+        return 0.0;
+    }
+
+    let q_middle = (q.0 + q.1) * 0.5;
+    let projected_middle = (start + end) * 0.5;
+    (1.0 - 2.0 * length(q_middle - projected_middle) / projected).max(0.0)
+}
+
+fn project(point: Vector, (start, end): (Vector, Vector)) -> Vector {
+    let line = end - start;
+    let t = dot(point - start, line) / dot(line, line);
+    start + line * t
+}
+
+/// Resamples a path into the given number of segments of equal length.
+fn resample(path: &[Vector], subdivisions: usize) -> Vec<Vector> {
+    let total: f32 = path.windows(2).map(|pair| length(pair[1] - pair[0])).sum();
+    if total < f32::EPSILON {
+        return vec![path[0]; subdivisions + 1];
+    }
+
+    let segment_length = total / subdivisions as f32;
+    let mut result = vec![path[0]];
+    let mut remaining = segment_length;
+    for pair in path.windows(2) {
+        let (mut start, end) = (pair[0], pair[1]);
+        let mut distance = length(end - start);
+        while distance >= remaining && result.len() < subdivisions {
+            start = start + (end - start) * (remaining / distance);
+            result.push(start);
+            distance -= remaining;
+            remaining = segment_length;
+        }
+        remaining -= distance;
+    }
+    result.push(path[path.len() - 1]);
+
+    result
+}
+
+/// Moves every inner point of a path halfway towards the middle of its neighbours.
+fn smooth(path: &[Vector]) -> Vec<Vector> {
+    let mut result = path.to_vec();
+    for i in 1..path.len().saturating_sub(1) {
+        let middle = (path[i - 1] + path[i + 1]) * 0.5;
+        result[i] = (path[i] + middle) * 0.5;
+    }
+    result
+}
+
+fn length(vector: Vector) -> f32 {
+    (vector.x * vector.x + vector.y * vector.y).sqrt()
+}
+
+fn dot(a: Vector, b: Vector) -> f32 {
+    a.x * b.x + a.y * b.y
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(from: (f32, f32), to: (f32, f32)) -> Vec<Vector> {
+        vec![Vector::new(from.0, from.1), Vector::new(to.0, to.1)]
+    }
+
+    fn middle(path: &[Vector]) -> Vector {
+        path[path.len() / 2]
+    }
+
+    #[test]
+    fn resample_spaces_points_evenly() {
+        let path = [
+            Vector::new(0.0, 0.0),
+            Vector::new(3.0, 0.0),
+            Vector::new(4.0, 0.0),
+        ];
+        let resampled = resample(&path, 4);
+
+        assert_eq!(resampled.len(), 5);
+        for (i, point) in resampled.iter().enumerate() {
+            assert!((point.x - i as f32).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn parallel_paths_are_pulled_together() {
+        let paths = [
+            line((0.0, 0.0), (200.0, 0.0)),
+            line((0.0, 20.0), (200.0, 20.0)),
+        ];
+        let bundled = bundle_edges(&paths);
+
+        let distance = length(middle(&bundled[0]) - middle(&bundled[1]));
+        assert!(distance < 10.0, "paths are still {distance} apart");
+    }
+
+    #[test]
+    fn opposite_paths_are_pulled_together() {
+        let paths = [
+            line((0.0, 0.0), (200.0, 0.0)),
+            line((200.0, 20.0), (0.0, 20.0)),
+        ];
+        let bundled = bundle_edges(&paths);
+
+        let distance = length(middle(&bundled[0]) - middle(&bundled[1]));
+        assert!(distance < 10.0, "paths are still {distance} apart");
+    }
+
+    #[test]
+    fn ends_stay_in_place() {
+        let paths = [
+            line((0.0, 0.0), (200.0, 0.0)),
+            line((0.0, 20.0), (200.0, 20.0)),
+        ];
+        let bundled = bundle_edges(&paths);
+
+        for (path, original) in bundled.iter().zip(&paths) {
+            assert_eq!(path.first(), original.first());
+            assert_eq!(path.last(), original.last());
+        }
+    }
+
+    #[test]
+    fn incompatible_paths_stay_straight() {
+        let paths = [
+            line((0.0, 0.0), (200.0, 0.0)),
+            line((100.0, 50.0), (100.0, 250.0)),
+        ];
+        let bundled = bundle_edges(&paths);
+
+        assert!(bundled[0].iter().all(|point| point.y.abs() < 1e-3));
+        assert!(bundled[1]
+            .iter()
+            .all(|point| (point.x - 100.0).abs() < 1e-3));
+    }
+}
@@ -1,40 +1,107 @@
-use std::sync::Mutex;
+use std::sync::{Mutex, OnceLock};
 
 use iced::advanced::graphics::mesh::{Indexed, SolidVertex2D};
-use iced::advanced::renderer;
 use iced::advanced::widget::Tree;
-use iced::{advanced::Widget, Length, Point, Size, Vector};
+use iced::advanced::{layout, renderer, Clipboard, Layout, Shell};
+use iced::time::Instant;
+use iced::{
+    advanced::Widget, event, mouse, Border, Color, Element, Event, Length, Point, Rectangle, Size,
+    Vector,
+};
 
 use crate::{
     mesh_renderer::MeshRenderer,
-    node_element::{GraphNodeElement, ScalableWidget},
-    styles::connection::StyleSheet,
-    SocketRole,
+    node_element::{GraphNodeElement, ScalableWidget, SocketLayout, SocketLayoutState},
+    routing::{round_corners, route_orthogonal},
+    stroke::{fill, stroke, LineCap, LineJoin, Stroke, StrokeVertex},
+    styles::connection::{DashPattern, StyleSheet},
+    SocketRole, SocketSide,
 };
 
-pub struct Connection<Message, Theme>
+pub struct Connection<'a, Message, Theme, Renderer>
 where
     Theme: StyleSheet,
 {
     link: Link,
     width: f32,
+    width_mode: WidthMode,
+    min_width: Option<f32>,
     number_of_segments: usize,
+    curvature: f32,
+    corner_radius: f32,
+    path_style: ConnectionStyle,
+    waypoints: Vec<Point>,
+    arrowhead: bool,
+    chevrons: usize,
+    marker_size: f32,
+    gradient: bool,
+    flow: Option<Flow>,
+    label: Option<Element<'a, Message, Theme, Renderer>>,
+    label_position: f32,
+    label_min_scale: f32,
+    line_join: LineJoin,
+    line_cap: LineCap,
     style: Theme::Style,
 
     phantom_message: std::marker::PhantomData<Message>,
-    spline: Mutex<Vec<Vector>>,
+    path: Mutex<Path>,
+}
+
+/// The path of a connection as determined during layout.
+#[derive(Debug, Default)]
+struct Path {
+    /// The points of the path, relative to `origin`.
+    points: Vec<Vector>,
+    /// The index of the first point of every leg, i.e. of every part of the path between two
+    /// consecutive endpoints or waypoints.
+    leg_starts: Vec<usize>,
+    /// The position of the path's bounds, in the coordinate space of the sockets.
+    origin: Vector,
+    /// The distance from the end of the path at which the arrowhead's tip is placed, such that
+    /// it is not hidden by the blob of the end socket.
+    end_inset: f32,
+    /// The scale the path was laid out at.
+    scale: f32,
+    /// The colors of the sockets at the start and the end, if any.
+    start_color: Option<Color>,
+    end_color: Option<Color>,
 }
 
-impl<Message, Theme> Connection<Message, Theme>
+impl Path {
+    /// Returns the index of the leg the segment starting at the given point belongs to.
+    fn leg_of(&self, point_index: usize) -> usize {
+        self.leg_starts
+            .partition_point(|start| *start <= point_index)
+            .saturating_sub(1)
+    }
+}
+
+impl<'a, Message, Theme, Renderer> Connection<'a, Message, Theme, Renderer>
 where
     Theme: StyleSheet,
 {
     pub fn new(link: Link) -> Self {
         Connection {
-            spline: Mutex::new(Vec::new()),
+            path: Mutex::new(Path::default()),
             link,
             width: 1.2,
+            width_mode: WidthMode::default(),
+            min_width: None,
             number_of_segments: 20,
+            curvature: 1.0,
+            corner_radius: 10.0,
+            path_style: ConnectionStyle::default(),
+            waypoints: Vec::new(),
+            arrowhead: false,
+            chevrons: 0,
+            marker_size: 8.0,
+            gradient: false,
+            flow: None,
+            label: None,
+            label_position: 0.5,
+            label_min_scale: 0.5,
+            line_join: LineJoin::default(),
+            line_cap: LineCap::default(),
             phantom_message: std::marker::PhantomData,
             style: Default::default(),
         }
@@ -44,38 +111,320 @@ where
         Self::new(Link::from_unordered(first, second))
     }
 
+    /// Sets the width of the connection, in pixels or graph units depending on its
+    /// [`WidthMode`].
     pub fn width(mut self, width: f32) -> Self {
         self.width = width;
         self
     }
 
+    /// Sets whether the width of the connection stays the same on screen or scales with the zoom
+    /// level like the nodes do.
+    pub fn width_mode(mut self, width_mode: WidthMode) -> Self {
+        self.width_mode = width_mode;
+        self
+    }
+
+    /// Sets the width in pixels that [`WidthMode::World`] connections keep when zoomed out far,
+    /// such that they do not vanish.
+    pub fn min_width(mut self, min_width: Option<f32>) -> Self {
+        self.min_width = min_width;
+        self
+    }
+
+    /// Sets the number of points the curve is sampled at. Only used by
+    /// [`ConnectionStyle::Hermite`]; other styles are tessellated adaptively.
     pub fn number_of_segments(mut self, number_of_segments: usize) -> Self {
         self.number_of_segments = number_of_segments;
         self
     }
 
+    /// Sets how far the connection bulges out of the sockets it connects, relative to the distance
+    /// between them. At `0.0`, the connection is a straight line.
+    pub fn curvature(mut self, curvature: f32) -> Self {
+        self.curvature = curvature;
+        self
+    }
+
+    /// Sets the radius of the rounded corners of [`ConnectionStyle::Orthogonal`] connections.
+    pub fn corner_radius(mut self, corner_radius: f32) -> Self {
+        self.corner_radius = corner_radius;
+        self
+    }
+
+    pub fn path_style(mut self, path_style: ConnectionStyle) -> Self {
+        self.path_style = path_style;
+        self
+    }
+
+    /// Sets the points, in graph space, that the connection passes through on its way from the
+    /// start to the end. Every part of the path between two consecutive points follows the
+    /// connection's [`ConnectionStyle`].
+    pub fn waypoints(mut self, waypoints: Vec<Point>) -> Self {
+        self.waypoints = waypoints;
+        self
+    }
+
+    /// Draws an arrowhead at the end of the connection.
+    pub fn arrowhead(mut self, arrowhead: bool) -> Self {
+        self.arrowhead = arrowhead;
+        self
+    }
+
+    /// Draws the given number of chevrons pointing towards the end, evenly spaced along the
+    /// connection.
+    pub fn chevrons(mut self, chevrons: usize) -> Self {
+        self.chevrons = chevrons;
+        self
+    }
+
+    /// Sets the length of arrowheads and chevrons, in graph units.
+    pub fn marker_size(mut self, marker_size: f32) -> Self {
+        self.marker_size = marker_size;
+        self
+    }
+
+    /// Blends the color of the connection from the blob color of the start socket to that of the
+    /// end socket. Endpoints that are not sockets use the color from the style sheet.
+    pub fn gradient(mut self, gradient: bool) -> Self {
+        self.gradient = gradient;
+        self
+    }
+
+    /// Animates dashes or dots travelling along the connection from its start to its end, e.g. to
+    /// show that it is carrying data. While any connection in a
+    /// [`GraphContainer`](crate::GraphContainer) is animated, it is redrawn every frame.
+    pub fn flow(mut self, flow: Option<Flow>) -> Self {
+        self.flow = flow;
+        self
+    }
+
+    /// Shows the given element, e.g. a name or a condition, on a pill-shaped background on top of
+    /// the connection. It is not scaled with the zoom level, like the content of nodes.
+    pub fn label(mut self, label: impl Into<Element<'a, Message, Theme, Renderer>>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    /// Sets where along the connection the label is centered, from `0.0` at the start to `1.0` at
+    /// the end. Defaults to the midpoint.
+    pub fn label_position(mut self, label_position: f32) -> Self {
+        self.label_position = label_position.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Sets the zoom level below which the label is hidden, as it would cover too much of the
+    /// graph.
+    pub fn label_min_scale(mut self, label_min_scale: f32) -> Self {
+        self.label_min_scale = label_min_scale;
+        self
+    }
+
+    /// Returns whether the label is shown at the zoom level the connection was laid out at.
+    fn shows_label(&self) -> bool {
+        self.label.is_some()
+            && self.path.lock().expect("Could not lock mutex").scale >= self.label_min_scale
+    }
+
+    /// Sets how the connection is joined at bends.
+    pub fn line_join(mut self, line_join: LineJoin) -> Self {
+        self.line_join = line_join;
+        self
+    }
+
+    /// Sets how the ends of the connection, its dashes and its chevrons are drawn.
+    pub fn line_cap(mut self, line_cap: LineCap) -> Self {
+        self.line_cap = line_cap;
+        self
+    }
+
+    /// Returns the width of the stroke in pixels at the given scale, according to the width mode.
+    fn effective_width(&self, scale: f32) -> f32 {
+        match self.width_mode {
+            WidthMode::Screen => self.width,
+            WidthMode::World => (self.width * scale).max(self.min_width.unwrap_or(0.0)),
+        }
+    }
+
+    /// Generates the path through all waypoints, together with the index of the first point of
+    /// every leg.
+    fn generate_path(
+        &self,
+        from: ResolvedEndpoint,
+        to: ResolvedEndpoint,
+        scale: f32,
+        socket_state: &SocketLayoutState,
+    ) -> (Vec<Vector>, Vec<usize>) {
+        let stops: Vec<Vector> = std::iter::once(from.position)
+            .chain(
+                self.waypoints
+                    .iter()
+                    .map(|waypoint| Vector::new(waypoint.x * scale, waypoint.y * scale)),
+            )
+            .chain(std::iter::once(to.position))
+            .collect();
+
+        // Waypoints are passed through in the direction from the previous to the next stop
+        let tangent = |index: usize| {
+            let direction = normalize_vector(stops[index + 1] - stops[index - 1]);
+            match self.path_style {
+                ConnectionStyle::Orthogonal if direction.x.abs() >= direction.y.abs() => {
+                    Vector::new(direction.x.signum(), 0.0)
+                }
+                ConnectionStyle::Orthogonal => Vector::new(0.0, direction.y.signum()),
+                _ => direction,
+            }
+        };
+
+        // Connections from a node to itself loop around it, unless they are routed by waypoints
+        let self_loop_node = match self.link.node_pair() {
+            Some((start, end)) if start == end && self.waypoints.is_empty() => {
+                socket_state.nodes.get(start).copied()
+            }
+            _ => None,
+        };
+
+        let mut spline: Vec<Vector> = Vec::new();
+        let mut leg_starts = Vec::new();
+        for index in 0..stops.len() - 1 {
+            let leg_from = if index == 0 {
+                from
+            } else {
+                ResolvedEndpoint {
+                    position: stops[index],
+                    normal: tangent(index),
+                    color: None,
+                }
+            };
+            let leg_to = if index == stops.len() - 2 {
+                to
+            } else {
+                let normal = tangent(index + 1);
+                ResolvedEndpoint {
+                    position: stops[index + 1],
+                    normal: Vector::new(-normal.x, -normal.y),
+                    color: None,
+                }
+            };
+
+            let leg = match self_loop_node {
+                Some(node) => generate_self_loop(leg_from, leg_to, node, scale),
+                None => self.generate_leg(leg_from, leg_to, scale, &socket_state.nodes),
+            };
+            leg_starts.push(spline.len().saturating_sub(1));
+            let skip = if spline.is_empty() { 0 } else { 1 };
+            spline.extend(leg.into_iter().skip(skip));
+        }
+
+        (spline, leg_starts)
+    }
+
+    /// Generates the path of one leg of the connection.
+    fn generate_leg(
+        &self,
+        from: ResolvedEndpoint,
+        to: ResolvedEndpoint,
+        scale: f32,
+        obstacles: &[Rectangle],
+    ) -> Vec<Vector> {
+        match self.path_style {
+            #[allow(deprecated)]
+            ConnectionStyle::Hermite | ConnectionStyle::CatmullRom => {
+                generate_spline(from, to, self.curvature, scale, self.number_of_segments)
+            }
+            ConnectionStyle::Bezier => generate_bezier(from, to, self.curvature, scale),
+            ConnectionStyle::Orthogonal => round_corners(
+                &route_orthogonal(from, to, obstacles, ORTHOGONAL_MARGIN * scale),
+                self.corner_radius * scale,
+            ),
+            ConnectionStyle::Straight => vec![from.position, to.position],
+        }
+    }
+
     pub fn style(mut self, style: Theme::Style) -> Self {
         self.style = style;
         self
     }
 }
 
-pub fn connection<Message, Theme>(from: Point, to: Point) -> Connection<Message, Theme>
+/// Dashes or dots travelling along a [`Connection`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Flow {
+    /// The lengths of the dashes and of the gaps between them, in pixels. Dots have the length of
+    /// a dash as their diameter.
+    pub pattern: DashPattern,
+    /// Draws dots instead of dashes.
+    pub dots: bool,
+    /// The speed of the dashes, in pixels per second.
+    pub speed: f32,
+    /// The color of the dashes. If `None`, the color from the style sheet is used. The rest of
+    /// the connection is drawn translucently while it is animated.
+    pub color: Option<Color>,
+}
+
+impl Default for Flow {
+    fn default() -> Self {
+        Self {
+            pattern: DashPattern {
+                dash: 6.0,
+                gap: 6.0,
+            },
+            dots: false,
+            speed: 30.0,
+            color: None,
+        }
+    }
+}
+
+/// The units the width of a [`Connection`] is given in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WidthMode {
+    /// The width is given in pixels, and stays the same at every zoom level.
+    #[default]
+    Screen,
+    /// The width is given in graph units, and scales with the zoom level.
+    World,
+}
+
+/// The kind of path a [`Connection`] follows between its endpoints.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConnectionStyle {
+    /// A cubic Hermite curve that leaves and enters the sockets along their directions, sampled at
+    /// a fixed number of points.
+    #[default]
+    Hermite,
+    /// The previous name of [`ConnectionStyle::Hermite`], which draws the same curve.
+    #[deprecated(note = "use `Hermite`, which names the curve that is actually drawn")]
+    CatmullRom,
+    /// A cubic Bézier curve whose control points lie in front of the sockets, tessellated depending
+    /// on its on-screen size and curvature.
+    Bezier,
+    /// Horizontal and vertical segments with rounded corners, routed around the nodes.
+    Orthogonal,
+    /// A straight line; together with waypoints, a polyline.
+    Straight,
+}
+
+pub fn connection<'a, Message, Theme, Renderer>(
+    from: Point,
+    to: Point,
+) -> Connection<'a, Message, Theme, Renderer>
 where
     Theme: StyleSheet,
 {
     Connection::between(Endpoint::Absolute(from), Endpoint::Absolute(to))
 }
 
-impl<Message, Theme, Renderer> ScalableWidget<Message, Renderer> for Connection<Message, Theme>
+impl<'a, Message, Theme, Renderer> ScalableWidget<Message, Renderer>
+    for Connection<'a, Message, Theme, Renderer>
 where
     Theme: StyleSheet,
     Renderer: renderer::Renderer,
 {
     fn layout(
         &self,
-        _tree: &mut Tree,
-        _renderer: &Renderer,
+        tree: &mut Tree,
+        renderer: &Renderer,
         _limits: &iced::advanced::layout::Limits,
         scale: f32,
         socket_state: &mut super::node_element::SocketLayoutState,
@@ -84,38 +433,149 @@ where
         // this will cause a panic if there are further nodes
         socket_state.done = true;
 
-        let spline = generate_spline(
-            self.link.start.resolve(scale, socket_state),
-            1.0,
-            self.link.end.resolve(scale, socket_state),
-            self.number_of_segments,
-            1.0_f32,
-        );
+        let (from, to) = self.link.resolve(scale, socket_state);
+        let parallel_offset = socket_state.parallel_offset(&self.link) * scale;
+        let (spline, leg_starts) = match socket_state.bundled_path.take() {
+            // Bundled paths may have been computed in an earlier layout, so their ends are moved
+            // onto the sockets in case these have moved slightly since
+            Some(mut bundled) => {
+                if let Some(first) = bundled.first_mut() {
+                    *first = from.position;
+                }
+                if let Some(last) = bundled.last_mut() {
+                    *last = to.position;
+                }
+                (bundled, vec![0])
+            }
+            None => {
+                let (mut spline, leg_starts) = self.generate_path(from, to, scale, socket_state);
+                if parallel_offset != 0.0 {
+                    spread(&mut spline, parallel_offset);
+                }
+                (spline, leg_starts)
+            }
+        };
 
-        let spline_bounds = bounds_for_vectors(&spline);
+        // The bounds extend by half the stroke width around the path
+        let width = self.effective_width(scale);
+        let spline_bounds = bounds_for_vectors(&spline).expand(width / 2.0);
 
-        let spline = spline
+        let spline: Vec<Vector> = spline
             .iter()
             .map(|p| Vector::new(p.x - spline_bounds.x, p.y - spline_bounds.y))
             .collect();
 
-        let node = iced::advanced::layout::Node::new(Size::new(
-            (spline_bounds.width + self.width).ceil(),
-            (spline_bounds.height + self.width).ceil(),
-        ));
+        // The label is laid out at its natural size inside a padded pill, centered on the path
+        let children = match &self.label {
+            Some(label) => {
+                let content = label
+                    .as_widget()
+                    .layout(
+                        &mut tree.children[0],
+                        renderer,
+                        &layout::Limits::new(Size::ZERO, Size::INFINITY),
+                    )
+                    .move_to(Point::new(LABEL_PADDING_X, LABEL_PADDING_Y));
+                let size = content
+                    .size()
+                    .expand(Size::new(LABEL_PADDING_X * 2.0, LABEL_PADDING_Y * 2.0));
+                let center = point_along(&spline, path_length(&spline) * self.label_position)
+                    .map_or(Vector::new(0.0, 0.0), |(point, _)| point);
+
+                vec![layout::Node::with_children(size, vec![content]).move_to(
+                    Point::new(center.x, center.y) - Vector::new(size.width, size.height) * 0.5,
+                )]
+            }
+            None => Vec::new(),
+        };
+
+        let node = iced::advanced::layout::Node::with_children(
+            Size::new(spline_bounds.width.ceil(), spline_bounds.height.ceil()),
+            children,
+        );
+
+        let end_inset = match self.link.end() {
+            Endpoint::Socket(logical) => {
+                let socket = logical.layout(socket_state);
+                match socket.side {
+                    SocketSide::Left | SocketSide::Right => socket.rect.width / 2.0,
+                    SocketSide::Top | SocketSide::Bottom => socket.rect.height / 2.0,
+                }
+            }
+            Endpoint::Absolute(_) => 0.0,
+        };
 
-        let mut self_state = self.spline.lock().expect("Could not lock mutex");
-        *self_state = spline;
+        let mut self_state = self.path.lock().expect("Could not lock mutex");
+        *self_state = Path {
+            points: spline,
+            leg_starts,
+            origin: Vector::new(spline_bounds.x, spline_bounds.y),
+            end_inset,
+            scale,
+            start_color: from.color,
+            end_color: to.color,
+        };
 
         node.translate(Vector::new(spline_bounds.x, spline_bounds.y))
     }
+
+    fn link(&self) -> Option<&Link> {
+        Some(&self.link)
+    }
+
+    fn waypoints(&self) -> &[Point] {
+        &self.waypoints
+    }
+
+    fn is_animated(&self) -> bool {
+        self.flow.is_some()
+    }
+
+    fn bundleable_path(&self, scale: f32, socket_state: &SocketLayoutState) -> Option<Vec<Vector>> {
+        // Connections routed by waypoints keep their route, and dangling connections change too
+        // often for bundling to keep up
+        if !self.waypoints.is_empty() || self.link.node_pair().is_none() {
+            return None;
+        }
+
+        let (from, to) = self.link.resolve(scale, socket_state);
+        Some(self.generate_path(from, to, scale, socket_state).0)
+    }
+
+    fn path_distance(&self, point: Point) -> Option<(f32, usize)> {
+        let path = self.path.lock().expect("Could not lock mutex");
+        let point = Vector::new(point.x, point.y) - path.origin;
+
+        path.points
+            .windows(2)
+            .enumerate()
+            .map(|(index, segment)| {
+                (
+                    distance_to_segment(point, segment[0], segment[1]),
+                    path.leg_of(index),
+                )
+            })
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+    }
 }
 
-impl<Message, Theme, Renderer> Widget<Message, Theme, Renderer> for Connection<Message, Theme>
+impl<'a, Message, Theme, Renderer> Widget<Message, Theme, Renderer>
+    for Connection<'a, Message, Theme, Renderer>
 where
     Theme: StyleSheet,
     Renderer: renderer::Renderer + MeshRenderer,
 {
+    fn children(&self) -> Vec<Tree> {
+        self.label.iter().map(Tree::new).collect()
+    }
+
+    fn diff(&self, tree: &mut Tree) {
+        match &self.label {
+            Some(label) => tree.diff_children(std::slice::from_ref(label)),
+            None => tree.children.clear(),
+        }
+    }
+
     fn layout(
         &self,
         _tree: &mut Tree,
@@ -127,34 +587,199 @@ where
 
     fn draw(
         &self,
-        _tree: &iced::advanced::widget::Tree,
+        tree: &iced::advanced::widget::Tree,
         renderer: &mut Renderer,
         theme: &Theme,
-        _renderer_style: &renderer::Style,
+        renderer_style: &renderer::Style,
         layout: iced::advanced::Layout<'_>,
-        _cursor: iced::mouse::Cursor,
-        _viewport: &iced::Rectangle,
+        cursor: iced::mouse::Cursor,
+        viewport: &iced::Rectangle,
     ) {
         let bounds = layout.bounds();
         let style = theme.appearance(&self.style);
 
-        let spline = self.spline.lock().unwrap();
-        let (vertices, indices) = line_to_polygon(&spline, self.width / 2.0);
+        let path = self.path.lock().expect("Could not lock mutex");
+        let length = path_length(&path.points);
+        let strokes = match style.dash_pattern {
+            Some(pattern) => dash_path(&path.points, pattern, 0.0),
+            None => vec![(0.0, path.points.clone())],
+        };
 
-        let buffers = Indexed {
-            vertices: vertices
+        let half_width = self.effective_width(path.scale) / 2.0;
+        let mut mesh = Stroke::default();
+        for (start, points) in strokes {
+            mesh.extend(stroke(
+                &points,
+                half_width,
+                start,
+                self.line_join,
+                self.line_cap,
+            ));
+        }
+
+        let marker_size = self.marker_size * path.scale;
+        if self.arrowhead {
+            if let Some((tip, direction)) = point_along(&path.points, length - path.end_inset) {
+                mesh.extend(fill(&arrowhead(tip, direction, marker_size), length));
+            }
+        }
+        for i in 0..self.chevrons {
+            let distance = length * (i + 1) as f32 / (self.chevrons + 1) as f32;
+            if let Some((center, direction)) = point_along(&path.points, distance) {
+                let mut chevron = stroke(
+                    &chevron(center, direction, marker_size),
+                    half_width,
+                    distance,
+                    self.line_join,
+                    self.line_cap,
+                );
+                // Chevrons have a single color, rather than following the gradient across them
+                chevron
+                    .vertices
+                    .iter_mut()
+                    .for_each(|vertex| vertex.distance = distance);
+                mesh.extend(chevron);
+            }
+        }
+
+        let color = style.color.unwrap();
+        let (mut start_color, mut end_color) = if self.gradient {
+            (
+                path.start_color.unwrap_or(color),
+                path.end_color.unwrap_or(color),
+            )
+        } else {
+            (color, color)
+        };
+        if self.flow.is_some() {
+            start_color.a *= FLOW_BACKGROUND_ALPHA;
+            end_color.a *= FLOW_BACKGROUND_ALPHA;
+        }
+
+        let mut buffers = Indexed {
+            vertices: mesh
+                .vertices
                 .iter()
-                .map(|p| SolidVertex2D {
-                    position: [p.x, p.y],
-                    color: iced::advanced::graphics::color::pack(style.color.unwrap()),
+                .map(|vertex| {
+                    let t = if length > 0.0 {
+                        vertex.distance / length
+                    } else {
+                        0.0
+                    };
+                    solid_vertex(vertex, mix(start_color, end_color, t.clamp(0.0, 1.0)))
                 })
                 .collect(),
-            indices,
+            indices: mesh.indices,
         };
 
+        if let Some(flow) = self.flow {
+            let epoch = *ANIMATION_EPOCH.get_or_init(Instant::now);
+            let offset = Instant::now().duration_since(epoch).as_secs_f32() * flow.speed;
+            let flow_color = flow.color.unwrap_or(color);
+
+            for (start, dash) in dash_path(&path.points, flow.pattern, offset) {
+                let dash = if flow.dots {
+                    let Some((center, _)) = point_along(&dash, path_length(&dash) / 2.0) else {
+                        continue;
+                    };
+                    fill(&dot(center, flow.pattern.dash / 2.0), start)
+                } else {
+                    stroke(&dash, half_width, start, self.line_join, self.line_cap)
+                };
+
+                let offset = buffers.vertices.len() as u32;
+                buffers.vertices.extend(
+                    dash.vertices
+                        .iter()
+                        .map(|vertex| solid_vertex(vertex, flow_color)),
+                );
+                buffers
+                    .indices
+                    .extend(dash.indices.into_iter().map(|index| index + offset));
+            }
+        }
+
         renderer.with_translation(Vector::new(bounds.x, bounds.y), |renderer| {
             renderer.draw_buffers(buffers);
         });
+        drop(path);
+
+        if let Some(label) = self.label.as_ref().filter(|_| self.shows_label()) {
+            let pill = layout.children().next().unwrap();
+            let pill_bounds = pill.bounds();
+            // Meshes are drawn on top of quads and text within a layer, so the label needs its own
+            renderer.with_layer(pill_bounds, |renderer| {
+                renderer.fill_quad(
+                    renderer::Quad {
+                        bounds: pill_bounds,
+                        border: Border {
+                            color: Color::TRANSPARENT,
+                            width: 0.0,
+                            radius: (pill_bounds.height / 2.0).into(),
+                        },
+                        ..renderer::Quad::default()
+                    },
+                    style.label_background,
+                );
+                label.as_widget().draw(
+                    &tree.children[0],
+                    renderer,
+                    theme,
+                    renderer_style,
+                    pill.children().next().unwrap(),
+                    cursor,
+                    viewport,
+                );
+            });
+        }
+    }
+
+    fn on_event(
+        &mut self,
+        tree: &mut Tree,
+        event: Event,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        renderer: &Renderer,
+        clipboard: &mut dyn Clipboard,
+        shell: &mut Shell<'_, Message>,
+        viewport: &Rectangle,
+    ) -> event::Status {
+        if !self.shows_label() {
+            return event::Status::Ignored;
+        }
+
+        let content = layout.children().next().unwrap().children().next().unwrap();
+        self.label.as_mut().unwrap().as_widget_mut().on_event(
+            &mut tree.children[0],
+            event,
+            content,
+            cursor,
+            renderer,
+            clipboard,
+            shell,
+            viewport,
+        )
+    }
+
+    fn mouse_interaction(
+        &self,
+        tree: &Tree,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        viewport: &Rectangle,
+        renderer: &Renderer,
+    ) -> mouse::Interaction {
+        match self.label.as_ref().filter(|_| self.shows_label()) {
+            Some(label) => label.as_widget().mouse_interaction(
+                &tree.children[0],
+                layout.children().next().unwrap().children().next().unwrap(),
+                cursor,
+                viewport,
+                renderer,
+            ),
+            None => mouse::Interaction::default(),
+        }
     }
 
     fn size(&self) -> Size<Length> {
@@ -162,19 +787,19 @@ where
     }
 }
 
-impl<'a, Message, Theme, Renderer> From<Connection<Message, Theme>>
+impl<'a, Message, Theme, Renderer> From<Connection<'a, Message, Theme, Renderer>>
     for GraphNodeElement<'a, Message, Theme, Renderer>
 where
     Message: 'a,
     Theme: StyleSheet + 'a,
     Renderer: renderer::Renderer + MeshRenderer + 'a,
 {
-    fn from(node: Connection<Message, Theme>) -> Self {
+    fn from(node: Connection<'a, Message, Theme, Renderer>) -> Self {
         Self::new(node)
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Link {
     start: Endpoint,
     end: Endpoint,
@@ -217,6 +842,16 @@ impl Link {
         }
     }
 
+    /// Returns the indices of the nodes at the start and at the end, if both are sockets.
+    pub(crate) fn node_pair(&self) -> Option<(usize, usize)> {
+        match (&self.start, &self.end) {
+            (Endpoint::Socket(start), Endpoint::Socket(end)) => {
+                Some((start.node_index, end.node_index))
+            }
+            _ => None,
+        }
+    }
+
     pub fn start(&self) -> &Endpoint {
         &self.start
     }
@@ -225,6 +860,38 @@ impl Link {
         &self.end
     }
 
+    /// Resolves both endpoints. Absolute endpoints face the other endpoint's socket, if there is
+    /// one, such that dangling connections look like they would once connected.
+    pub(crate) fn resolve(
+        &self,
+        scale: f32,
+        socket_state: &super::node_element::SocketLayoutState,
+    ) -> (ResolvedEndpoint, ResolvedEndpoint) {
+        let start = self.start.resolve(scale, socket_state);
+        let end = self.end.resolve(scale, socket_state);
+
+        let start_normal = start
+            .normal
+            .or(end.normal.map(|normal| Vector::new(-normal.x, -normal.y)))
+            .unwrap_or(Vector::new(1.0, 0.0));
+        let end_normal = end
+            .normal
+            .unwrap_or(Vector::new(-start_normal.x, -start_normal.y));
+
+        (
+            ResolvedEndpoint {
+                position: start.position,
+                normal: start_normal,
+                color: start.color,
+            },
+            ResolvedEndpoint {
+                position: end.position,
+                normal: end_normal,
+                color: end.color,
+            },
+        )
+    }
+
     pub fn unwrap_sockets(&self) -> (&LogicalEndpoint, &LogicalEndpoint) {
         if let Endpoint::Socket(l_start) = &self.start {
             if let Endpoint::Socket(l_end) = &self.end {
@@ -236,7 +903,7 @@ impl Link {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Endpoint {
     Absolute(Point),
     Socket(LogicalEndpoint),
@@ -259,61 +926,259 @@ impl Endpoint {
         Self::socket(node_index, SocketRole::In, socket_index)
     }
 
-    fn resolve(&self, scale: f32, socket_state: &super::node_element::SocketLayoutState) -> Vector {
+    fn resolve(
+        &self,
+        scale: f32,
+        socket_state: &super::node_element::SocketLayoutState,
+    ) -> PartialEndpoint {
         match self {
-            Endpoint::Absolute(point) => Vector::new(point.x * scale, point.y * scale),
+            Endpoint::Absolute(point) => PartialEndpoint {
+                position: Vector::new(point.x * scale, point.y * scale),
+                normal: None,
+                color: None,
+            },
             Endpoint::Socket(logical) => {
-                let node_sockets = match logical.role {
-                    SocketRole::In => &socket_state.inputs,
-                    SocketRole::Out => &socket_state.outputs,
-                };
-
-                match node_sockets.get(logical.node_index) {
-                    Some(sockets) => match sockets.get(logical.socket_index) {
-                        Some(rect) => Vector::new(rect.center_x(), rect.center_y()),
-                        None => panic!("socket index {} out of bounds for role {:?} of node {}; only found {} socket(s)", logical.socket_index, logical.role, logical.node_index, sockets.len())
-                    }
-                    None => panic!("node index {} out of bounds for role {:?}; only found {} node(s)", logical.node_index, logical.role, node_sockets.len())
+                let socket = logical.layout(socket_state);
+                PartialEndpoint {
+                    position: Vector::new(socket.rect.center_x(), socket.rect.center_y()),
+                    normal: Some(socket.side.normal()),
+                    color: socket.color,
                 }
             }
         }
     }
 }
 
+/// An endpoint resolved on its own; absolute endpoints do not know their direction yet.
+struct PartialEndpoint {
+    position: Vector,
+    normal: Option<Vector>,
+    color: Option<Color>,
+}
+
+/// The on-screen position of a connection endpoint, together with the unit vector pointing away
+/// from the node it is attached to; connections leave and enter endpoints along this direction.
 #[derive(Debug, Clone, Copy)]
+pub(crate) struct ResolvedEndpoint {
+    pub(crate) position: Vector,
+    pub(crate) normal: Vector,
+    /// The color of the socket's blob, if the endpoint is a socket.
+    pub(crate) color: Option<Color>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct LogicalEndpoint {
     pub node_index: usize,
     pub role: SocketRole,
     pub socket_index: usize,
 }
 
-fn line_to_polygon(points: &[Vector], width: f32) -> (Vec<Vector>, Vec<u32>) {
-    let mut result = Vec::new();
-    let mut indices = Vec::new();
+impl LogicalEndpoint {
+    fn layout<'s>(
+        &self,
+        socket_state: &'s super::node_element::SocketLayoutState,
+    ) -> &'s SocketLayout {
+        let node_sockets = match self.role {
+            SocketRole::In => &socket_state.inputs,
+            SocketRole::Out => &socket_state.outputs,
+        };
 
-    let mut last = points[0];
-    for point in points.iter().skip(1) {
-        let dir = normalize_vector(*point - last);
-        let normal = Vector::new(dir.y, -dir.x);
+        match node_sockets.get(self.node_index) {
+            Some(sockets) => match sockets.get(self.socket_index) {
+                Some(socket) => socket,
+                None => panic!("socket index {} out of bounds for role {:?} of node {}; only found {} socket(s)", self.socket_index, self.role, self.node_index, sockets.len())
+            }
+            None => panic!("node index {} out of bounds for role {:?}; only found {} node(s)", self.node_index, self.role, node_sockets.len())
+        }
+    }
+}
+
+/// Converts a vertex of a stroke to one of the given color, faded out by its coverage.
+fn solid_vertex(vertex: &StrokeVertex, mut color: Color) -> SolidVertex2D {
+    color.a *= vertex.alpha;
+    SolidVertex2D {
+        position: [vertex.position.x, vertex.position.y],
+        color: iced::advanced::graphics::color::pack(color),
+    }
+}
 
-        result.push(last + normal * width);
-        result.push(*point + normal * width);
-        result.push(*point - normal * width);
-        result.push(last - normal * width);
+/// Generates the corners of a triangle of the given length whose tip is at `tip`, pointing in
+/// `direction`.
+fn arrowhead(tip: Vector, direction: Vector, size: f32) -> Vec<Vector> {
+    let normal = Vector::new(-direction.y, direction.x);
+    let base = tip - direction * size;
 
-        let start = result.len() as u32 - 4;
-        indices.push(start);
-        indices.push(start + 1);
-        indices.push(start + 2);
+    vec![
+        tip,
+        base + normal * (size / 2.0),
+        base - normal * (size / 2.0),
+    ]
+}
 
-        indices.push(start);
-        indices.push(start + 2);
-        indices.push(start + 3);
+/// Generates a V-shaped line of the given length centered at `center`, pointing in `direction`.
+fn chevron(center: Vector, direction: Vector, size: f32) -> Vec<Vector> {
+    let normal = Vector::new(-direction.y, direction.x);
+    let tip = center + direction * (size / 4.0);
+    let back = center - direction * (size / 4.0);
 
-        last = *point;
+    vec![
+        back + normal * (size / 2.0),
+        tip,
+        back - normal * (size / 2.0),
+    ]
+}
+
+/// The horizontal and vertical space between a label and the edge of its background, in pixels.
+const LABEL_PADDING_X: f32 = 6.0;
+const LABEL_PADDING_Y: f32 = 2.0;
+
+/// The opacity of the parts of an animated connection between its dashes.
+const FLOW_BACKGROUND_ALPHA: f32 = 0.35;
+
+/// The number of segments used to approximate the circles of dotted flows.
+const DOT_SEGMENTS: u32 = 12;
+
+/// The point in time animations are relative to, shared such that all connections move in sync.
+static ANIMATION_EPOCH: OnceLock<Instant> = OnceLock::new();
+
+/// Splits a path into the pieces covered by the dashes of the given pattern, with the pattern
+/// shifted forward along the path by `offset`. Every piece is returned together with its distance
+/// from the start of the path.
+fn dash_path(points: &[Vector], pattern: DashPattern, offset: f32) -> Vec<(f32, Vec<Vector>)> {
+    let period = pattern.dash + pattern.gap;
+    if pattern.dash <= 0.0 || pattern.gap <= 0.0 {
+        return vec![(0.0, points.to_vec())];
+    }
+
+    let mut dashes = Vec::new();
+    let mut current: Vec<Vector> = Vec::new();
+    let mut current_start = 0.0;
+    let mut segment_start = 0.0;
+    // The position within the pattern, where dashes span from 0 to `pattern.dash`
+    let mut phase = (-offset).rem_euclid(period);
+
+    for segment in points.windows(2) {
+        let (start, end) = (segment[0], segment[1]);
+        let length = vector_length(end - start);
+        let direction = normalize_vector(end - start);
+
+        let mut travelled = 0.0;
+        while travelled < length {
+            let in_dash = phase < pattern.dash;
+            let boundary = if in_dash { pattern.dash } else { period };
+            let reaches_boundary = boundary - phase <= length - travelled;
+            let step = if reaches_boundary {
+                boundary - phase
+            } else {
+                length - travelled
+            };
+
+            if in_dash {
+                if current.is_empty() {
+                    current.push(start + direction * travelled);
+                    current_start = segment_start + travelled;
+                }
+                current.push(start + direction * (travelled + step));
+            }
+            travelled += step;
+
+            if reaches_boundary {
+                if in_dash {
+                    dashes.push((current_start, std::mem::take(&mut current)));
+                    phase = pattern.dash;
+                } else {
+                    phase = 0.0;
+                }
+            } else {
+                phase += step;
+            }
+        }
+        segment_start += length;
+    }
+
+    if current.len() >= 2 {
+        dashes.push((current_start, current));
+    }
+
+    dashes
+}
+
+/// Generates the outline of a circle.
+fn dot(center: Vector, radius: f32) -> Vec<Vector> {
+    (0..DOT_SEGMENTS)
+        .map(|i| {
+            let angle = i as f32 / DOT_SEGMENTS as f32 * std::f32::consts::TAU;
+            center + Vector::new(angle.cos(), angle.sin()) * radius
+        })
+        .collect()
+}
+
+/// Bends a path sideways by up to `offset`, perpendicular to the line between its end points,
+/// while keeping the end points in place. Used to tell apart connections between the same nodes.
+fn spread(points: &mut [Vector], offset: f32) {
+    let (Some(&first), Some(&last)) = (points.first(), points.last()) else {
+        return;
+    };
+    let chord = normalize_vector(last - first);
+    let length = path_length(points);
+    if length == 0.0 || (chord.x == 0.0 && chord.y == 0.0) {
+        return;
+    }
+    let normal = Vector::new(-chord.y, chord.x);
+
+    let mut travelled = 0.0;
+    let mut previous = first;
+    for point in points.iter_mut() {
+        travelled += vector_length(*point - previous);
+        previous = *point;
+        let weight = (travelled / length * std::f32::consts::PI).sin();
+        *point = *point + normal * (offset * weight);
+    }
+}
+
+fn path_length(points: &[Vector]) -> f32 {
+    points
+        .windows(2)
+        .map(|segment| vector_length(segment[1] - segment[0]))
+        .sum()
+}
+
+fn mix(from: Color, to: Color, t: f32) -> Color {
+    Color {
+        r: from.r + (to.r - from.r) * t,
+        g: from.g + (to.g - from.g) * t,
+        b: from.b + (to.b - from.b) * t,
+        a: from.a + (to.a - from.a) * t,
     }
+}
+
+/// Returns the point at the given distance along the path, together with the direction of the
+/// path at that point.
+fn point_along(points: &[Vector], distance: f32) -> Option<(Vector, Vector)> {
+    let mut remaining = distance.max(0.0);
+    for segment in points.windows(2) {
+        let delta = segment[1] - segment[0];
+        let length = vector_length(delta);
+        if length == 0.0 {
+            continue;
+        }
+
+        if remaining <= length {
+            let direction = normalize_vector(delta);
+            return Some((segment[0] + direction * remaining, direction));
+        }
+        remaining -= length;
+    }
+
+    // Distances beyond the end of the path are clamped to its end
+    points.windows(2).rev().find_map(|segment| {
+        let delta = segment[1] - segment[0];
+        (vector_length(delta) > 0.0).then(|| (segment[1], normalize_vector(delta)))
+    })
+}
 
-    (result, indices)
+fn vector_length(vector: Vector) -> f32 {
+    (vector.x * vector.x + vector.y * vector.y).sqrt()
 }
 
 fn normalize_vector(vector: Vector) -> Vector {
@@ -325,55 +1190,140 @@ fn normalize_vector(vector: Vector) -> Vector {
     }
 }
 
-fn dot_vector(vector: Vector, other: Vector) -> f32 {
-    vector.x * other.x + vector.y * other.y
+/// The smallest distance (in graph units) used to determine the tangents of a spline, such that
+/// even connections between sockets close to each other visibly leave their nodes.
+const MIN_TANGENT_DISTANCE: f32 = 50.0;
+
+/// The distance (in graph units) that orthogonal connections keep from nodes.
+const ORTHOGONAL_MARGIN: f32 = 20.0;
+
+/// The distance (in graph units) that connections from a node to itself keep from the node.
+const SELF_LOOP_MARGIN: f32 = 25.0;
+
+/// Generates a loop from a socket of `node` around the node to another of its sockets.
+fn generate_self_loop(
+    from: ResolvedEndpoint,
+    to: ResolvedEndpoint,
+    node: Rectangle,
+    scale: f32,
+) -> Vec<Vector> {
+    let margin = SELF_LOOP_MARGIN * scale;
+    round_corners(&route_orthogonal(from, to, &[node], margin), margin)
 }
 
+/// Generates a spline that leaves `from` along its normal and enters `to` against its normal.
+///
+/// This is a cubic Hermite curve, i.e. a Catmull-Rom spline segment whose end tangents are given by
+/// the socket directions instead of by neighbouring points. Its tangents are as long as the
+/// distance between the endpoints, times `curvature`.
 fn generate_spline(
-    from: Vector,
-    control_scale: f32,
-    to: Vector,
+    from: ResolvedEndpoint,
+    to: ResolvedEndpoint,
+    curvature: f32,
+    scale: f32,
     number_of_segments: usize,
-    alpha: f32,
 ) -> Vec<Vector> {
     let mut spline = Vec::new();
 
+    let delta = to.position - from.position;
+    let distance = (delta.x * delta.x + delta.y * delta.y)
+        .sqrt()
+        .max(MIN_TANGENT_DISTANCE * scale);
+    let from_tangent = from.normal * (distance * curvature);
+    let to_tangent = to.normal * (-distance * curvature);
+
     for i in 0..number_of_segments {
         let t = i as f32 / (number_of_segments - 1) as f32;
-        let p = catmull_rom(
-            Vector::new(from.x - control_scale, from.y),
-            from,
-            to,
-            Vector::new(to.x + control_scale, to.y),
+        spline.push(hermite(
+            from.position,
+            from_tangent,
+            to.position,
+            to_tangent,
             t,
-            alpha,
-        );
-        spline.push(p);
+        ));
     }
 
     spline
 }
 
-// Code taken and adapted from https://en.wikipedia.org/wiki/Centripetal_Catmull%E2%80%93Rom_spline
-fn get_t(t: f32, alpha: f32, p0: Vector, p1: Vector) -> f32 {
-    let d = p1 - p0;
-    let a = dot_vector(d, d);
-    let b = a.powf(alpha * 0.5);
-    b + t
-}
-
-fn catmull_rom(p0: Vector, p1: Vector, p2: Vector, p3: Vector, t: f32, alpha: f32) -> Vector {
-    let t0 = 0.0;
-    let t1 = get_t(t0, alpha, p0, p1);
-    let t2 = get_t(t1, alpha, p1, p2);
-    let t3 = get_t(t2, alpha, p2, p3);
-    let t = t1 + (t2 - t1) * t;
-    let a1 = p0 * ((t1 - t) / (t1 - t0)) + p1 * ((t - t0) / (t1 - t0));
-    let a2 = p1 * ((t2 - t) / (t2 - t1)) + p2 * ((t - t1) / (t2 - t1));
-    let a3 = p2 * ((t3 - t) / (t3 - t2)) + p3 * ((t - t2) / (t3 - t2));
-    let b1 = a1 * ((t2 - t) / (t2 - t0)) + a2 * ((t - t0) / (t2 - t0));
-    let b2 = a2 * ((t3 - t) / (t3 - t1)) + a3 * ((t - t1) / (t3 - t1));
-    b1 * ((t2 - t) / (t2 - t1)) + b2 * ((t - t1) / (t2 - t1))
+fn hermite(p0: Vector, m0: Vector, p1: Vector, m1: Vector, t: f32) -> Vector {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    p0 * (2.0 * t3 - 3.0 * t2 + 1.0)
+        + m0 * (t3 - 2.0 * t2 + t)
+        + p1 * (-2.0 * t3 + 3.0 * t2)
+        + m1 * (t3 - t2)
+}
+
+/// The maximum distance in pixels between a flattened Bézier curve and the actual curve.
+const BEZIER_TOLERANCE: f32 = 0.2;
+
+/// The maximum number of times a Bézier curve is subdivided while flattening it.
+const BEZIER_MAX_DEPTH: u32 = 10;
+
+/// Generates a cubic Bézier curve that leaves `from` along its normal and enters `to` against its
+/// normal. The control points are placed in front of the sockets, at half the distance between
+/// the endpoints along the socket direction (or further, for sockets close to each other).
+fn generate_bezier(
+    from: ResolvedEndpoint,
+    to: ResolvedEndpoint,
+    curvature: f32,
+    scale: f32,
+) -> Vec<Vector> {
+    let delta = to.position - from.position;
+    let along = (delta.x * from.normal.x + delta.y * from.normal.y).abs();
+    let offset = (along * 0.5).max(MIN_TANGENT_DISTANCE * scale) * curvature;
+
+    let control_points = [
+        from.position,
+        from.position + from.normal * offset,
+        to.position + to.normal * offset,
+        to.position,
+    ];
+
+    let mut points = vec![from.position];
+    flatten_bezier(control_points, BEZIER_MAX_DEPTH, &mut points);
+    points
+}
+
+/// Appends points approximating the given cubic Bézier curve (excluding its start point) by
+/// recursively subdividing it until every piece is flat enough.
+fn flatten_bezier(control_points: [Vector; 4], depth: u32, points: &mut Vec<Vector>) {
+    let [p0, p1, p2, p3] = control_points;
+
+    // The curve lies within the convex hull of its control points, so it is flat enough if the
+    // inner control points are close to the chord
+    let is_flat =
+        distance_to_segment(p1, p0, p3).max(distance_to_segment(p2, p0, p3)) <= BEZIER_TOLERANCE;
+    if is_flat || depth == 0 {
+        points.push(p3);
+        return;
+    }
+
+    // Split the curve in half using de Casteljau's algorithm
+    let p01 = (p0 + p1) * 0.5;
+    let p12 = (p1 + p2) * 0.5;
+    let p23 = (p2 + p3) * 0.5;
+    let p012 = (p01 + p12) * 0.5;
+    let p123 = (p12 + p23) * 0.5;
+    let mid = (p012 + p123) * 0.5;
+
+    flatten_bezier([p0, p01, p012, mid], depth - 1, points);
+    flatten_bezier([mid, p123, p23, p3], depth - 1, points);
+}
+
+fn distance_to_segment(point: Vector, start: Vector, end: Vector) -> f32 {
+    let segment = end - start;
+    let length_squared = segment.x * segment.x + segment.y * segment.y;
+    let t = if length_squared == 0.0 {
+        0.0
+    } else {
+        (((point.x - start.x) * segment.x + (point.y - start.y) * segment.y) / length_squared)
+            .clamp(0.0, 1.0)
+    };
+
+    let offset = point - (start + segment * t);
+    (offset.x * offset.x + offset.y * offset.y).sqrt()
 }
 
 fn bounds_for_vectors(points: &[Vector]) -> iced::Rectangle {
@@ -0,0 +1,167 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use crate::{Endpoint, Link};
+
+/// Finds all elementary cycles in the graph formed by the given links.
+///
+/// Each cycle is reported as the list of node indices along it, starting at the smallest node
+/// index, such that every node links to the next one and the last one links back to the first.
+/// Links with absolute endpoints are ignored, as are multiple links between the same pair of
+/// nodes. Note that the number of cycles may grow exponentially with the size of the graph.
+pub fn find_cycles(links: &[Link]) -> Vec<Vec<usize>> {
+    let adjacency = adjacency(links);
+    let mut cycles = Vec::new();
+
+    for &start in adjacency.keys() {
+        let mut path = vec![start];
+        let mut on_path = HashSet::from([start]);
+        collect_cycles(
+            &adjacency,
+            start,
+            start,
+            &mut path,
+            &mut on_path,
+            &mut cycles,
+        );
+    }
+
+    cycles
+}
+
+/// Returns whether adding `link` to the graph formed by `links` would close a cycle.
+pub fn would_create_cycle(links: &[Link], link: &Link) -> bool {
+    match (link.start(), link.end()) {
+        (Endpoint::Socket(start), Endpoint::Socket(end)) => {
+            reachable_nodes(links, end.node_index, false).contains(&start.node_index)
+        }
+        _ => false,
+    }
+}
+
+/// Returns the set of nodes that can be reached from `node_index` by following links, including
+/// `node_index` itself. If `reverse` is set, links are followed backwards instead.
+pub(crate) fn reachable_nodes(links: &[Link], node_index: usize, reverse: bool) -> HashSet<usize> {
+    let mut adjacency = adjacency(links);
+    if reverse {
+        let mut reversed: BTreeMap<usize, BTreeSet<usize>> = BTreeMap::new();
+        for (from, targets) in adjacency {
+            for to in targets {
+                reversed.entry(to).or_default().insert(from);
+            }
+        }
+        adjacency = reversed;
+    }
+
+    let mut reached = HashSet::from([node_index]);
+    let mut stack = vec![node_index];
+    while let Some(current) = stack.pop() {
+        for &next in adjacency.get(&current).into_iter().flatten() {
+            if reached.insert(next) {
+                stack.push(next);
+            }
+        }
+    }
+
+    reached
+}
+
+fn adjacency(links: &[Link]) -> BTreeMap<usize, BTreeSet<usize>> {
+    let mut adjacency: BTreeMap<usize, BTreeSet<usize>> = BTreeMap::new();
+
+    for link in links {
+        if let (Endpoint::Socket(start), Endpoint::Socket(end)) = (link.start(), link.end()) {
+            adjacency
+                .entry(start.node_index)
+                .or_default()
+                .insert(end.node_index);
+        }
+    }
+
+    adjacency
+}
+
+fn collect_cycles(
+    adjacency: &BTreeMap<usize, BTreeSet<usize>>,
+    start: usize,
+    current: usize,
+    path: &mut Vec<usize>,
+    on_path: &mut HashSet<usize>,
+    cycles: &mut Vec<Vec<usize>>,
+) {
+    for &next in adjacency.get(&current).into_iter().flatten() {
+        // Only consider nodes greater than the start, such that every cycle is found exactly once,
+        // namely when starting from its smallest node
+        if next == start {
+            cycles.push(path.clone());
+        } else if next > start && on_path.insert(next) {
+            path.push(next);
+            collect_cycles(adjacency, start, next, path, on_path, cycles);
+            path.pop();
+            on_path.remove(&next);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use iced::Point;
+
+    use super::*;
+
+    fn link(from: usize, to: usize) -> Link {
+        Link::new(Endpoint::start(from, 0), Endpoint::end(to, 0))
+    }
+
+    #[test]
+    fn no_cycles_in_dag() {
+        let links = [link(0, 1), link(1, 2), link(0, 2)];
+
+        assert!(find_cycles(&links).is_empty());
+        assert!(!would_create_cycle(&links, &link(0, 3)));
+        assert!(would_create_cycle(&links, &link(2, 0)));
+    }
+
+    #[test]
+    fn self_edge_is_a_cycle() {
+        let links = [link(0, 1), link(1, 1)];
+
+        assert_eq!(find_cycles(&links), vec![vec![1]]);
+        assert!(would_create_cycle(&links[..1], &link(0, 0)));
+    }
+
+    #[test]
+    fn disjoint_cycles_are_found_once_each() {
+        let links = [
+            link(0, 1),
+            link(1, 2),
+            link(2, 0),
+            link(4, 3),
+            link(3, 4),
+            link(3, 4),
+        ];
+
+        assert_eq!(find_cycles(&links), vec![vec![0, 1, 2], vec![3, 4]]);
+    }
+
+    #[test]
+    fn absolute_endpoints_are_ignored() {
+        let links = [
+            link(0, 1),
+            Link::new(Endpoint::start(1, 0), Endpoint::Absolute(Point::ORIGIN)),
+        ];
+
+        assert!(find_cycles(&links).is_empty());
+        assert!(!would_create_cycle(
+            &links,
+            &Link::new(Endpoint::Absolute(Point::ORIGIN), Endpoint::end(0, 0))
+        ));
+    }
+
+    #[test]
+    fn reachable_nodes_follow_direction() {
+        let links = [link(0, 1), link(1, 2), link(3, 1)];
+
+        assert_eq!(reachable_nodes(&links, 1, false), HashSet::from([1, 2]));
+        assert_eq!(reachable_nodes(&links, 1, true), HashSet::from([0, 1, 3]));
+    }
+}
@@ -1,14 +1,18 @@
 use iced::{
     advanced::{
-        layout, renderer,
+        layout,
+        mouse::click,
+        overlay, renderer,
         widget::{self, Operation},
         Clipboard, Layout, Shell, Widget,
-    }, border::Radius, event, mouse, Background, Border, Color, Element, Event, Length, Point, Rectangle, Size, Vector
+    }, border::Radius, event, keyboard, mouse, window::{self, RedrawRequest}, Background, Border, Color, Element, Event, Length, Pixels, Point, Rectangle, Size, Vector
 };
-use std::collections::VecDeque;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::Mutex;
 
+use crate::bundling::bundle_edges;
 use crate::connection::LogicalEndpoint;
+use crate::graph::reachable_nodes;
 use crate::node_element::SocketLayoutState;
 use crate::{
     matrix::Matrix,
@@ -33,16 +37,105 @@ where
     on_connect: Option<Box<dyn Fn(Link) -> Message + 'a>>,
     on_disconnect: Option<Box<dyn Fn(LogicalEndpoint, Point) -> Message + 'a>>,
     on_dangling: Option<Box<dyn Fn(Option<(LogicalEndpoint, Link)>) -> Message + 'a>>,
+    can_connect: Option<Box<dyn Fn(&LogicalEndpoint, &LogicalEndpoint) -> bool + 'a>>,
+    on_waypoints_changed: Option<Box<dyn Fn(Link, Vec<Point>) -> Message + 'a>>,
+    on_reroute: Option<Box<dyn Fn(Link, Point) -> Message + 'a>>,
     dangling_source: Option<LogicalEndpoint>,
+    prevent_cycles: bool,
+    allow_self_loops: bool,
+    snap_radius: f32,
+    parallel_spacing: f32,
+    edge_bundling: bool,
 
     phantom_message: std::marker::PhantomData<Message>,
     socket_state: Mutex<SocketLayoutState>,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SocketHint {
+    Compatible,
+    Incompatible,
+    Cycle,
+}
+
 struct GraphContainerState {
     drag_start_position: Option<Point>,
+    last_click: Option<click::Click>,
+    modifiers: keyboard::Modifiers,
+    /// The index of the element and of its waypoint that is being dragged.
+    dragged_waypoint: Option<(usize, usize)>,
+    /// The inputs of the last bundling, and the resulting bundled paths in graph space together
+    /// with the indices of their connections.
+    bundle_cache: Option<(BundleKey, Vec<(usize, Vec<Vector>)>)>,
+}
+
+/// The graph-space inputs that the bundled paths of the connections are computed from.
+struct BundleKey {
+    /// The bounds of every node.
+    nodes: Vec<Rectangle>,
+    /// The blob rects of all input sockets, followed by those of all output sockets.
+    sockets: Vec<Rectangle>,
+    /// The link of every connection, except for the dangling one, and whether it has waypoints.
+    /// Connections with waypoints are not bundled, so where their waypoints lie does not matter.
+    links: Vec<Option<(Link, bool)>>,
 }
 
+impl BundleKey {
+    fn new(socket_state: &SocketLayoutState, scale: f32, links: Vec<Option<(Link, bool)>>) -> Self {
+        let descale = |rect: &Rectangle| {
+            Rectangle::new(
+                Point::new(rect.x / scale, rect.y / scale),
+                Size::new(rect.width / scale, rect.height / scale),
+            )
+        };
+        BundleKey {
+            nodes: socket_state.nodes.iter().map(descale).collect(),
+            sockets: socket_state
+                .inputs
+                .iter()
+                .chain(&socket_state.outputs)
+                .flatten()
+                .map(|socket| descale(&socket.rect))
+                .collect(),
+            links,
+        }
+    }
+
+    /// Returns whether both keys lead to the same bundled paths. The rects are compared with some
+    /// tolerance, as descaling them after zooming introduces rounding errors.
+    fn matches(&self, other: &BundleKey) -> bool {
+        let close = |a: &Rectangle, b: &Rectangle| {
+            (a.x - b.x).abs() < BUNDLE_KEY_TOLERANCE
+                && (a.y - b.y).abs() < BUNDLE_KEY_TOLERANCE
+                && (a.width - b.width).abs() < BUNDLE_KEY_TOLERANCE
+                && (a.height - b.height).abs() < BUNDLE_KEY_TOLERANCE
+        };
+        self.links == other.links
+            && self.nodes.len() == other.nodes.len()
+            && self.sockets.len() == other.sockets.len()
+            && self
+                .nodes
+                .iter()
+                .zip(&other.nodes)
+                .all(|(a, b)| close(a, b))
+            && self
+                .sockets
+                .iter()
+                .zip(&other.sockets)
+                .all(|(a, b)| close(a, b))
+    }
+}
+
+/// The distance in graph units by which the rects in two [`BundleKey`]s may differ while still
+/// matching.
+const BUNDLE_KEY_TOLERANCE: f32 = 0.01;
+
+/// The radius in pixels of the handles drawn at the waypoints of connections.
+const WAYPOINT_RADIUS: f32 = 4.0;
+
+/// The distance in pixels from a connection within which double-clicking adds a waypoint.
+const WAYPOINT_INSERT_DISTANCE: f32 = 5.0;
+
 impl<'a, Message, Theme, Renderer> GraphContainer<'a, Message, Theme, Renderer>
 where
     Theme: StyleSheet,
@@ -55,6 +148,9 @@ where
             on_connect: None,
             on_disconnect: None,
             on_dangling: None,
+            can_connect: None,
+            on_waypoints_changed: None,
+            on_reroute: None,
             matrix: Matrix::identity(),
             width: Length::Shrink,
             height: Length::Shrink,
@@ -63,11 +159,21 @@ where
             style: Default::default(),
             content,
             dangling_source: None,
+            prevent_cycles: false,
+            allow_self_loops: false,
+            snap_radius: 0.0,
+            parallel_spacing: 0.0,
+            edge_bundling: false,
 
             phantom_message: std::marker::PhantomData,
             socket_state: Mutex::new(SocketLayoutState {
                 inputs: vec![],
                 outputs: vec![],
+                nodes: vec![],
+                parallel_edges: HashMap::new(),
+                parallel_spacing: 0.0,
+                dangling: false,
+                bundled_path: None,
                 done: false,
             }),
         }
@@ -113,6 +219,72 @@ where
         self
     }
 
+    /// Sets a predicate that decides whether two sockets may be connected to each other.
+    ///
+    /// The predicate is called with the output socket first and the input socket second. It is
+    /// only consulted for pairs that pass the built-in checks (opposite roles, and different
+    /// nodes unless [`allow_self_loops`](Self::allow_self_loops) is set).
+    /// While a dangling connection is being dragged, sockets it may be connected to are
+    /// highlighted, and all others are dimmed.
+    pub fn can_connect<F>(mut self, f: F) -> Self
+    where
+        F: 'a + Fn(&LogicalEndpoint, &LogicalEndpoint) -> bool,
+    {
+        self.can_connect = Some(Box::new(f));
+        self
+    }
+
+    /// Makes the waypoints of connections editable: double-clicking a connection adds a waypoint,
+    /// dragging a waypoint moves it, and double-clicking a waypoint removes it. The closure
+    /// receives the link of the edited connection and its new waypoints, in graph space.
+    ///
+    /// If [`on_reroute`](Self::on_reroute) is set as well, a plain double-click on a connection
+    /// inserts a reroute instead, and waypoints are added with Shift+double-click.
+    pub fn on_waypoints_changed<F>(mut self, f: F) -> Self
+    where
+        F: 'a + Fn(Link, Vec<Point>) -> Message,
+    {
+        self.on_waypoints_changed = Some(Box::new(f));
+        self
+    }
+
+    /// Lets users insert reroutes by double-clicking a connection. The closure receives the link
+    /// of the connection and the position of the double-click, in graph space; it is up to the
+    /// application to add a [`Reroute`](crate::Reroute) there and to split the connection in two.
+    ///
+    /// Double-clicking a connection thus has two meanings when
+    /// [`on_waypoints_changed`](Self::on_waypoints_changed) is set as well:
+    ///
+    /// - a plain double-click inserts a reroute, and
+    /// - Shift+double-click inserts a waypoint.
+    ///
+    /// Double-clicking an existing waypoint always removes it.
+    pub fn on_reroute<F>(mut self, f: F) -> Self
+    where
+        F: 'a + Fn(Link, Point) -> Message,
+    {
+        self.on_reroute = Some(Box::new(f));
+        self
+    }
+
+    /// Sets the distance, in graph units, by which connections between the same pair of nodes are
+    /// bent apart from each other, such that each of them stays visible. `0.0` lets them overlap.
+    pub fn parallel_spacing(mut self, parallel_spacing: f32) -> Self {
+        self.parallel_spacing = parallel_spacing;
+        self
+    }
+
+    /// Bundles connections that run alongside each other, which reduces clutter in dense graphs.
+    /// Connections with waypoints and the dangling connection are left as they are.
+    ///
+    /// The bundled paths are computed by a simulation that takes a while for large graphs. It is
+    /// only run again when a node or a connection changes, and not while a node is being dragged,
+    /// in which case the paths are updated once the node is dropped.
+    pub fn edge_bundling(mut self, edge_bundling: bool) -> Self {
+        self.edge_bundling = edge_bundling;
+        self
+    }
+
     pub fn matrix(mut self, m: Matrix) -> Self {
         self.matrix = m;
         self
@@ -143,24 +315,299 @@ where
         self
     }
 
+    /// Sets the socket a connection is currently being dragged from. The dangling connection that
+    /// follows the cursor is expected to come after all other connections with this socket as an
+    /// endpoint, such that it can be told apart from them.
     pub fn dangling_source(mut self, dangling_source: Option<LogicalEndpoint>) -> Self {
         self.dangling_source = dangling_source;
         self
     }
 
+    /// Rejects connections that would close a cycle in the graph formed by the links of all
+    /// connections in this container. Sockets that would close a cycle are marked while a
+    /// dangling connection is being dragged.
+    pub fn prevent_cycles(mut self, prevent_cycles: bool) -> Self {
+        self.prevent_cycles = prevent_cycles;
+        self
+    }
+
+    /// Allows connecting an output socket of a node to an input socket of the same node. Such
+    /// connections are drawn as a loop around the node. They are only made when the dangling
+    /// connection is released right over one of the node's sockets, not anywhere on the node.
+    pub fn allow_self_loops(mut self, allow_self_loops: bool) -> Self {
+        self.allow_self_loops = allow_self_loops;
+        self
+    }
+
+    /// Sets the distance in screen pixels within which a dangling connection snaps to the nearest
+    /// compatible socket. Releasing the mouse button while snapped connects to that socket.
+    pub fn snap_radius(mut self, snap_radius: impl Into<Pixels>) -> Self {
+        self.snap_radius = snap_radius.into().0;
+        self
+    }
+
+    /// Returns the index of the dangling connection in the content, if there is one.
+    fn dangling_index(&self) -> Option<usize> {
+        let source = self.dangling_source?;
+        self.content.iter().rposition(|element| {
+            element.as_scalable_widget().link().is_some_and(|link| {
+                [link.start(), link.end()].into_iter().any(
+                    |endpoint| matches!(endpoint, Endpoint::Socket(logical) if *logical == source),
+                )
+            })
+        })
+    }
+
+    /// Returns the bundled paths of the connections by their index, in the coordinate space of the
+    /// sockets. These are only recomputed when the graph itself has changed since the last call.
+    ///
+    /// As bundling is slow for large graphs, it is not redone while an element is dragged. The
+    /// previous paths are kept until the drag ends, as long as the same connections are bundled.
+    fn bundled_paths(
+        &self,
+        state: &mut GraphContainerState,
+        children: &[widget::Tree],
+        scale: f32,
+        socket_state: &SocketLayoutState,
+    ) -> HashMap<usize, Vec<Vector>> {
+        let dangling_index = self.dangling_index();
+        let links = self
+            .content
+            .iter()
+            .enumerate()
+            .map(|(index, element)| {
+                let element = element.as_scalable_widget();
+                let link = element.link().filter(|_| Some(index) != dangling_index)?;
+                Some((link.clone(), !element.waypoints().is_empty()))
+            })
+            .collect();
+        let key = BundleKey::new(socket_state, scale, links);
+        let dragging = self
+            .content
+            .iter()
+            .zip(children)
+            .any(|(element, tree)| element.as_scalable_widget().is_dragged(tree));
+
+        let up_to_date = state.bundle_cache.as_ref().is_some_and(|(cached, _)| {
+            cached.matches(&key) || (dragging && cached.links == key.links)
+        });
+        if !up_to_date {
+            let (indices, paths): (Vec<usize>, Vec<Vec<Vector>>) = key
+                .links
+                .iter()
+                .enumerate()
+                .filter(|(_, link)| link.is_some())
+                .filter_map(|(index, _)| {
+                    let path = self.content[index]
+                        .as_scalable_widget()
+                        .bundleable_path(scale, socket_state)?;
+                    Some((index, path.into_iter().map(|p| p * (1.0 / scale)).collect()))
+                })
+                .unzip();
+
+            let bundled = if paths.len() > 1 {
+                indices.into_iter().zip(bundle_edges(&paths)).collect()
+            } else {
+                Vec::new()
+            };
+            state.bundle_cache = Some((key, bundled));
+        }
+
+        state
+            .bundle_cache
+            .iter()
+            .flat_map(|(_, bundled)| bundled)
+            .map(|(index, path)| (*index, path.iter().map(|p| *p * scale).collect()))
+            .collect()
+    }
+
+    /// Hides the tooltips of all elements when a drag starts, as the elements do not see the
+    /// cursor moving while the graph container handles the drag.
+    fn dismiss_tooltips(&self, children: &mut [widget::Tree], shell: &mut Shell<'_, Message>) {
+        let mut dismissed = false;
+        for (element, tree) in self.content.iter().zip(children) {
+            dismissed |= element.as_scalable_widget().dismiss_tooltip(tree);
+        }
+        if dismissed {
+            shell.invalidate_layout();
+        }
+    }
+
+    fn links(&self) -> Vec<Link> {
+        self.content
+            .iter()
+            .filter_map(|element| element.as_scalable_widget().link())
+            .cloned()
+            .collect()
+    }
+
+    /// Returns the nodes that would close a cycle if they were connected to `source`.
+    fn cycle_nodes(&self, source: LogicalEndpoint) -> HashSet<usize> {
+        if !self.prevent_cycles {
+            return HashSet::new();
+        }
+
+        // A new link closes a cycle if its end node can already reach its start node
+        reachable_nodes(
+            &self.links(),
+            source.node_index,
+            source.role == SocketRole::Out,
+        )
+    }
+
+    fn socket_hint(
+        &self,
+        source: LogicalEndpoint,
+        target: LogicalEndpoint,
+        cycle_nodes: &HashSet<usize>,
+    ) -> SocketHint {
+        // Don't allow connecting input to input or output to output sockets,
+        // and don't allow connecting a node to itself unless self-loops are enabled
+        if source.role == target.role
+            || (source.node_index == target.node_index && !self.allow_self_loops)
+        {
+            return SocketHint::Incompatible;
+        }
+
+        let compatible = match &self.can_connect {
+            Some(f) => match source.role {
+                SocketRole::Out => f(&source, &target),
+                SocketRole::In => f(&target, &source),
+            },
+            None => true,
+        };
+
+        if !compatible {
+            SocketHint::Incompatible
+        } else if cycle_nodes.contains(&target.node_index) {
+            SocketHint::Cycle
+        } else {
+            SocketHint::Compatible
+        }
+    }
+
+    /// Finds the socket a dangling connection from `source` would be connected to if it were
+    /// released at `cursor_position`: the nearest compatible socket within the snap radius, or
+    /// otherwise the first compatible socket of the node under the cursor.
+    fn find_target(
+        &self,
+        socket_state: &SocketLayoutState,
+        node_bounds: &[Rectangle],
+        source: LogicalEndpoint,
+        cursor_position: Point,
+    ) -> Option<LogicalEndpoint> {
+        let cycle_nodes = self.cycle_nodes(source);
+        let (role, node_sockets) = match source.role {
+            SocketRole::In => (SocketRole::Out, &socket_state.outputs),
+            SocketRole::Out => (SocketRole::In, &socket_state.inputs),
+        };
+        let is_compatible = |target: LogicalEndpoint| {
+            self.socket_hint(source, target, &cycle_nodes) == SocketHint::Compatible
+        };
+
+        // The stacked blobs of collapsed nodes cannot be told apart, so they are never targeted
+        let mut nearest: Option<(f32, LogicalEndpoint)> = None;
+        for (node_index, sockets) in node_sockets.iter().enumerate() {
+            for (socket_index, socket) in sockets.iter().enumerate() {
+                if socket.stacked {
+                    continue;
+                }
+                let target = LogicalEndpoint {
+                    node_index,
+                    role,
+                    socket_index,
+                };
+
+                // A connection only loops back to its own node when it is dropped right on one of
+                // the node's sockets
+                let distance = distance_to_rect(socket.rect, cursor_position);
+                let snap_radius = if node_index == source.node_index {
+                    0.0
+                } else {
+                    self.snap_radius
+                };
+                if distance <= snap_radius
+                    && nearest.is_none_or(|(nearest_distance, _)| distance < nearest_distance)
+                    && is_compatible(target)
+                {
+                    nearest = Some((distance, target));
+                }
+            }
+        }
+
+        if let Some((_, target)) = nearest {
+            return Some(target);
+        }
+
+        // Nodes are drawn in order, so the last node containing the cursor is the topmost one
+        let node_index = node_bounds
+            .iter()
+            .rposition(|bounds| bounds.contains(cursor_position))
+            .filter(|node_index| *node_index != source.node_index)?;
+        node_sockets
+            .get(node_index)?
+            .iter()
+            .enumerate()
+            .filter(|(_, socket)| !socket.stacked)
+            .map(|(socket_index, _)| LogicalEndpoint {
+                node_index,
+                role,
+                socket_index,
+            })
+            .find(|target| is_compatible(*target))
+    }
+
+    /// Finds the topmost waypoint handle at the given position, given in the coordinate space of
+    /// the sockets.
+    fn waypoint_at(&self, scale: f32, position: Point) -> Option<(usize, usize)> {
+        self.content
+            .iter()
+            .enumerate()
+            .rev()
+            .find_map(|(element_index, element)| {
+                let waypoint_index =
+                    element
+                        .as_scalable_widget()
+                        .waypoints()
+                        .iter()
+                        .position(|waypoint| {
+                            Point::new(waypoint.x * scale, waypoint.y * scale).distance(position)
+                                <= WAYPOINT_RADIUS
+                        })?;
+                Some((element_index, waypoint_index))
+            })
+    }
+
+    /// Produces the message for changing the waypoints of the element at `element_index`.
+    fn edit_waypoints(
+        &self,
+        element_index: usize,
+        edit: impl FnOnce(&mut Vec<Point>),
+    ) -> Option<Message> {
+        let f = self.on_waypoints_changed.as_ref()?;
+        let widget = self.content.get(element_index)?.as_scalable_widget();
+        let link = widget.link()?.clone();
+
+        let mut waypoints = widget.waypoints().to_vec();
+        edit(&mut waypoints);
+        Some(f(link, waypoints))
+    }
+
     fn try_emit_dangling(
         &self,
         shell: &mut Shell<'_, Message>,
         cursor_position: Point,
         source: LogicalEndpoint,
+        target: Option<LogicalEndpoint>,
     ) {
         if let Some(f) = &self.on_dangling {
+            let end = match target {
+                Some(target) => Endpoint::Socket(target),
+                None => Endpoint::Absolute(cursor_position),
+            };
             shell.publish(f(Some((
                 source,
-                Link::from_unordered(
-                    Endpoint::Socket(source),
-                    Endpoint::Absolute(cursor_position),
-                ),
+                Link::from_unordered(Endpoint::Socket(source), end),
             ))));
         }
     }
@@ -210,6 +657,10 @@ where
     fn state(&self) -> widget::tree::State {
         widget::tree::State::new(GraphContainerState {
             drag_start_position: None,
+            last_click: None,
+            modifiers: keyboard::Modifiers::default(),
+            dragged_waypoint: None,
+            bundle_cache: None,
         })
     }
 
@@ -236,8 +687,36 @@ where
             .lock()
             .expect("should be able to lock socket state mutex in layout()");
         socket_layout_state.clear();
+        socket_layout_state.parallel_spacing = self.parallel_spacing;
+        let dangling_index = self.dangling_index();
+        socket_layout_state.count_parallel_edges(
+            self.content
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| Some(*index) != dangling_index)
+                .filter_map(|(_, element)| element.as_scalable_widget().link()),
+        );
 
+        // The bundled paths are computed once all nodes are laid out, right before the first
+        // connection, and handed to the connections through the socket state
+        let mut bundled_paths: Option<HashMap<usize, Vec<Vector>>> = None;
         for (node_index, node) in self.content.iter().enumerate() {
+            socket_layout_state.dangling = Some(node_index) == dangling_index;
+            if self.edge_bundling
+                && bundled_paths.is_none()
+                && node.as_scalable_widget().link().is_some()
+            {
+                bundled_paths = Some(self.bundled_paths(
+                    tree.state.downcast_mut::<GraphContainerState>(),
+                    &tree.children,
+                    scale,
+                    &socket_layout_state,
+                ));
+            }
+            socket_layout_state.bundled_path = bundled_paths
+                .as_mut()
+                .and_then(|paths| paths.remove(&node_index));
+
             let mut node = node.as_scalable_widget().layout(
                 &mut tree.children[node_index],
                 _renderer,
@@ -275,6 +754,28 @@ where
         });
     }
 
+    fn overlay<'b>(
+        &'b mut self,
+        tree: &'b mut widget::Tree,
+        layout: Layout<'_>,
+        renderer: &Renderer,
+        translation: Vector,
+    ) -> Option<overlay::Element<'b, Message, Theme, Renderer>> {
+        let children: Vec<_> = self
+            .content
+            .iter_mut()
+            .zip(&mut tree.children)
+            .zip(layout.children())
+            .filter_map(|((child, state), layout)| {
+                child
+                    .as_widget_mut()
+                    .overlay(state, layout, renderer, translation)
+            })
+            .collect();
+
+        (!children.is_empty()).then(|| overlay::Group::with_children(children).overlay())
+    }
+
     fn on_event(
         &mut self,
         tree: &mut widget::Tree,
@@ -293,6 +794,20 @@ where
             .lock()
             .expect("should be able to lock socket state mutex in on_event()");
 
+        if let Event::Keyboard(keyboard::Event::ModifiersChanged(modifiers)) = event {
+            state.modifiers = modifiers;
+        }
+
+        if let Event::Window(window::Event::RedrawRequested(_)) = event {
+            if self
+                .content
+                .iter()
+                .any(|element| element.as_scalable_widget().is_animated())
+            {
+                shell.request_redraw(RedrawRequest::NextFrame);
+            }
+        }
+
         // Socket-related processing
         if let Event::Mouse(mouse_event) = event {
             if let Some(cursor_position) = cursor.position_in(layout.bounds()) {
@@ -306,6 +821,19 @@ where
                     translated_cursor_position.y / scale,
                 );
 
+                // Bounds of the node bodies, in the same coordinate system as the sockets
+                let node_bounds: Vec<Rectangle> = layout
+                    .children()
+                    .take(socket_state.inputs.len())
+                    .map(|node_layout| {
+                        node_layout.bounds()
+                            - Vector::new(
+                                layout.bounds().x + offset.0,
+                                layout.bounds().y + offset.1,
+                            )
+                    })
+                    .collect();
+
                 // Find the socket we're hovering over
                 let mut hovered_socket: Option<LogicalEndpoint> = None;
                 for (role, node_sockets) in [
@@ -313,8 +841,8 @@ where
                     (SocketRole::Out, &socket_state.outputs),
                 ] {
                     for (node_index, sockets) in node_sockets.iter().enumerate() {
-                        for (socket_index, blob_rect) in sockets.iter().enumerate() {
-                            if blob_rect.contains(translated_cursor_position) {
+                        for (socket_index, socket) in sockets.iter().enumerate() {
+                            if !socket.stacked && socket.rect.contains(translated_cursor_position) {
                                 hovered_socket = Some(LogicalEndpoint {
                                     node_index,
                                     role,
@@ -327,6 +855,14 @@ where
 
                 match mouse_event {
                     mouse::Event::ButtonPressed(mouse::Button::Left) => {
+                        let click = click::Click::new(
+                            cursor_position,
+                            mouse::Button::Left,
+                            state.last_click,
+                        );
+                        state.last_click = Some(click);
+                        let is_double_click = matches!(click.kind(), click::Kind::Double);
+
                         if let Some(hovered_socket) = hovered_socket {
                             match hovered_socket.role {
                                 SocketRole::In => {
@@ -347,46 +883,116 @@ where
                                         shell,
                                         translated_descaled_cursor_position,
                                         hovered_socket,
+                                        None,
                                     );
                                 }
                             }
+                            self.dismiss_tooltips(&mut tree.children, shell);
                             status = event::Status::Captured;
+                        } else if let Some((element_index, waypoint_index)) = self
+                            .on_waypoints_changed
+                            .is_some()
+                            .then(|| self.waypoint_at(scale, translated_cursor_position))
+                            .flatten()
+                        {
+                            if is_double_click {
+                                if let Some(message) =
+                                    self.edit_waypoints(element_index, |waypoints| {
+                                        waypoints.remove(waypoint_index);
+                                    })
+                                {
+                                    shell.publish(message);
+                                }
+                            } else {
+                                state.dragged_waypoint = Some((element_index, waypoint_index));
+                                self.dismiss_tooltips(&mut tree.children, shell);
+                            }
+                            status = event::Status::Captured;
+                        } else if is_double_click {
+                            // Insert a reroute or a waypoint into the topmost connection near
+                            // the cursor
+                            let hit = self.content.iter().enumerate().rev().find_map(
+                                |(element_index, element)| {
+                                    let (distance, leg) = element
+                                        .as_scalable_widget()
+                                        .path_distance(translated_cursor_position)?;
+                                    (distance <= WAYPOINT_INSERT_DISTANCE)
+                                        .then_some((element_index, leg))
+                                },
+                            );
+                            let message =
+                                hit.and_then(|(element_index, leg)| match &self.on_reroute {
+                                    Some(f) if !state.modifiers.shift() => {
+                                        let link = self.content[element_index]
+                                            .as_scalable_widget()
+                                            .link()?
+                                            .clone();
+                                        Some(f(link, translated_descaled_cursor_position))
+                                    }
+                                    _ => self.edit_waypoints(element_index, |waypoints| {
+                                        waypoints.insert(
+                                            leg.min(waypoints.len()),
+                                            translated_descaled_cursor_position,
+                                        );
+                                    }),
+                                });
+                            if let Some(message) = message {
+                                shell.publish(message);
+                                status = event::Status::Captured;
+                            }
                         }
                     }
                     mouse::Event::CursorMoved { .. } => {
                         // Update the existing dangling connection, if it exists
                         if let Some(dangling_source) = self.dangling_source {
+                            let target = self.find_target(
+                                &socket_state,
+                                &node_bounds,
+                                dangling_source,
+                                translated_cursor_position,
+                            );
                             self.try_emit_dangling(
                                 shell,
                                 translated_descaled_cursor_position,
                                 dangling_source,
+                                target,
                             );
                             status = event::Status::Captured;
+                        } else if let Some((element_index, waypoint_index)) = state.dragged_waypoint
+                        {
+                            if let Some(message) = self.edit_waypoints(element_index, |waypoints| {
+                                if let Some(waypoint) = waypoints.get_mut(waypoint_index) {
+                                    *waypoint = translated_descaled_cursor_position;
+                                }
+                            }) {
+                                shell.publish(message);
+                            }
+                            status = event::Status::Captured;
                         }
                     }
                     mouse::Event::ButtonReleased(mouse::Button::Left) => {
-                        if let Some(dangling_source) = self.dangling_source {
+                        if state.dragged_waypoint.take().is_some() {
+                            status = event::Status::Captured;
+                        } else if let Some(dangling_source) = self.dangling_source {
                             // No matter what happens, the dangling connection needs to be removed
                             if let Some(f) = &self.on_dangling {
                                 shell.publish(f(None));
                             }
 
-                            // If we're hovering over a socket while releasing the button,
-                            // there's a chance we're about to make a connection
-                            if let Some(hovered_socket) = hovered_socket {
-                                // Don't allow connecting input to input or output to output
-                                // sockets, and don't allow connecting a node to itself.
-                                // This does not definitively detect cycles, but it's a start
-                                if dangling_source.role != hovered_socket.role
-                                    && dangling_source.node_index != hovered_socket.node_index
-                                {
-                                    if let Some(f) = &self.on_connect {
-                                        let link = Link::from_unordered(
-                                            Endpoint::Socket(dangling_source),
-                                            Endpoint::Socket(hovered_socket),
-                                        );
-                                        shell.publish(f(link));
-                                    }
+                            // If we're near a compatible socket while releasing the button,
+                            // we're about to make a connection
+                            if let Some(target) = self.find_target(
+                                &socket_state,
+                                &node_bounds,
+                                dangling_source,
+                                translated_cursor_position,
+                            ) {
+                                if let Some(f) = &self.on_connect {
+                                    let link = Link::from_unordered(
+                                        Endpoint::Socket(dangling_source),
+                                        Endpoint::Socket(target),
+                                    );
+                                    shell.publish(f(link));
                                 }
                             }
                             status = event::Status::Captured;
@@ -491,16 +1097,36 @@ where
         viewport: &Rectangle,
         renderer: &Renderer,
     ) -> mouse::Interaction {
-        self.content
+        let interactions: Vec<(bool, mouse::Interaction)> = self
+            .content
             .iter()
             .zip(&tree.children)
             .zip(layout.children())
             .map(|((child, state), layout)| {
-                child
-                    .as_widget()
-                    .mouse_interaction(state, layout, cursor, viewport, renderer)
+                (
+                    child.as_scalable_widget().is_dragged(state),
+                    child
+                        .as_widget()
+                        .mouse_interaction(state, layout, cursor, viewport, renderer),
+                )
+            })
+            .collect();
+
+        // An element being dragged keeps its cursor while passing over other elements. Otherwise
+        // the topmost element that has an interaction determines the cursor.
+        if let Some((_, interaction)) = interactions.iter().find(|(dragged, _)| *dragged) {
+            return *interaction;
+        }
+        interactions
+            .into_iter()
+            .rev()
+            .map(|(_, interaction)| interaction)
+            .find(|interaction| {
+                !matches!(
+                    interaction,
+                    mouse::Interaction::None | mouse::Interaction::Idle
+                )
             })
-            .max()
             .unwrap_or_default()
     }
 
@@ -561,6 +1187,16 @@ where
                 style.major_guidelines_color.unwrap(),
             );
 
+            let socket_state = self
+                .socket_state
+                .lock()
+                .expect("should be able to lock socket state mutex in draw()");
+            let socket_offset = Vector::new(bounds.x + offset.0, bounds.y + offset.1);
+            let cycle_nodes = self
+                .dangling_source
+                .map(|source| self.cycle_nodes(source))
+                .unwrap_or_default();
+
             let mut children_layout = layout.children();
             for i in 0..self.content.len() {
                 let layout = children_layout.next().unwrap();
@@ -579,6 +1215,10 @@ where
                     continue;
                 }
 
+                // Every element gets its own layer, such that the meshes of an element (e.g. socket
+                // blobs) are drawn below the quads of the elements on top of it
+                renderer.start_layer(bounds);
+
                 node.draw(
                     &state.children[i],
                     renderer,
@@ -588,6 +1228,48 @@ where
                     cursor,
                     viewport,
                 );
+
+                if self.on_waypoints_changed.is_some() {
+                    if let Some(color) = style.waypoint_color {
+                        for waypoint in self.content[i].as_scalable_widget().waypoints() {
+                            let center =
+                                Point::new(waypoint.x * scale, waypoint.y * scale) + socket_offset;
+                            draw_waypoint(renderer, center, color);
+                        }
+                    }
+                }
+
+                // While dragging a connection, mark which sockets of this node it may connect to
+                if let Some(dangling_source) = self.dangling_source {
+                    for (role, node_sockets) in [
+                        (SocketRole::In, &socket_state.inputs),
+                        (SocketRole::Out, &socket_state.outputs),
+                    ] {
+                        let Some(sockets) = node_sockets.get(i) else {
+                            continue;
+                        };
+
+                        for (socket_index, socket) in sockets.iter().enumerate() {
+                            let target = LogicalEndpoint {
+                                node_index: i,
+                                role,
+                                socket_index,
+                            };
+                            if target == dangling_source || socket.stacked {
+                                continue;
+                            }
+
+                            draw_socket_hint(
+                                renderer,
+                                socket.rect + socket_offset,
+                                self.socket_hint(dangling_source, target, &cycle_nodes),
+                                style,
+                            );
+                        }
+                    }
+                }
+
+                renderer.end_layer();
             }
         });
     }
@@ -625,6 +1307,85 @@ where
     );
 }
 
+fn draw_socket_hint<Renderer>(
+    renderer: &mut Renderer,
+    blob_rect: Rectangle,
+    hint: SocketHint,
+    style: Appearance,
+) where
+    Renderer: renderer::Renderer,
+{
+    let ring_color = match hint {
+        SocketHint::Compatible => style.compatible_socket_color,
+        SocketHint::Cycle => style.cycle_socket_color,
+        SocketHint::Incompatible => None,
+    };
+
+    if let Some(color) = ring_color {
+        let ring = blob_rect.expand(2.0);
+        renderer.fill_quad(
+            renderer::Quad {
+                bounds: ring,
+                border: Border {
+                    color,
+                    width: 2.0_f32,
+                    radius: Radius::new(ring.width.min(ring.height) / 2.0),
+                },
+                ..renderer::Quad::default()
+            },
+            Background::Color(Color::TRANSPARENT),
+        );
+    }
+
+    if hint != SocketHint::Compatible {
+        if let Some(color) = style.incompatible_socket_color {
+            renderer.fill_quad(
+                renderer::Quad {
+                    bounds: blob_rect,
+                    border: Border {
+                        color: Color::TRANSPARENT,
+                        width: 0.0_f32,
+                        radius: Radius::new(blob_rect.width.min(blob_rect.height) / 2.0),
+                    },
+                    ..renderer::Quad::default()
+                },
+                Background::Color(color),
+            );
+        }
+    }
+}
+
+fn draw_waypoint<Renderer>(renderer: &mut Renderer, center: Point, color: Color)
+where
+    Renderer: renderer::Renderer,
+{
+    renderer.fill_quad(
+        renderer::Quad {
+            bounds: Rectangle::new(
+                center - Vector::new(WAYPOINT_RADIUS, WAYPOINT_RADIUS),
+                Size::new(WAYPOINT_RADIUS * 2.0, WAYPOINT_RADIUS * 2.0),
+            ),
+            border: Border {
+                color: Color::TRANSPARENT,
+                width: 0.0_f32,
+                radius: Radius::new(WAYPOINT_RADIUS),
+            },
+            ..renderer::Quad::default()
+        },
+        Background::Color(color),
+    );
+}
+
+fn distance_to_rect(rect: Rectangle, point: Point) -> f32 {
+    let dx = (rect.x - point.x)
+        .max(point.x - (rect.x + rect.width))
+        .max(0.0);
+    let dy = (rect.y - point.y)
+        .max(point.y - (rect.y + rect.height))
+        .max(0.0);
+    (dx * dx + dy * dy).sqrt()
+}
+
 fn draw_guidelines<Renderer>(
     renderer: &mut Renderer,
     bounds: Rectangle,
@@ -717,3 +1478,91 @@ fn normalize_scale(scale: f32) -> f32 {
         scale
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::node_element::SocketLayout;
+    use crate::SocketSide;
+
+    use super::*;
+
+    /// Lays out two nodes of the given size, 100 units apart, with one output and one input
+    /// socket at their middles, as they would be at the given scale.
+    fn socket_state(node_size: Size, scale: f32) -> SocketLayoutState {
+        let mut state = GraphContainer::<(), iced::Theme, iced::Renderer>::new(vec![])
+            .socket_state
+            .into_inner()
+            .expect("Could not lock mutex");
+        for (index, side) in [SocketSide::Right, SocketSide::Left]
+            .into_iter()
+            .enumerate()
+        {
+            let node = Rectangle::new(
+                Point::new(index as f32 * 100.0 * scale, 0.0),
+                Size::new(node_size.width * scale, node_size.height * scale),
+            );
+            let x = match side {
+                SocketSide::Right => node.x + node.width,
+                _ => node.x,
+            };
+            let socket = SocketLayout {
+                rect: Rectangle::new(
+                    Point::new(x - 5.0 * scale, node.center_y() - 5.0 * scale),
+                    Size::new(10.0 * scale, 10.0 * scale),
+                ),
+                side,
+                color: None,
+                stacked: false,
+            };
+            let (inputs, outputs) = match side {
+                SocketSide::Right => (vec![], vec![socket]),
+                _ => (vec![socket], vec![]),
+            };
+            state.inputs.push(inputs);
+            state.outputs.push(outputs);
+            state.nodes.push(node);
+        }
+        state
+    }
+
+    fn links() -> Vec<Option<(Link, bool)>> {
+        vec![Some((
+            Link::new(Endpoint::start(0, 0), Endpoint::end(1, 0)),
+            false,
+        ))]
+    }
+
+    #[test]
+    fn bundle_key_survives_zooming() {
+        let key = BundleKey::new(&socket_state(Size::new(50.0, 30.0), 1.0), 1.0, links());
+
+        for scale in [0.3, 0.7, 1.9] {
+            let zoomed = socket_state(Size::new(50.0, 30.0), scale);
+            assert!(key.matches(&BundleKey::new(&zoomed, scale, links())));
+        }
+    }
+
+    #[test]
+    fn bundle_key_changes_with_node_size() {
+        let key = BundleKey::new(&socket_state(Size::new(50.0, 30.0), 1.0), 1.0, links());
+
+        // Resizing moves the sockets
+        let resized = socket_state(Size::new(80.0, 30.0), 1.0);
+        assert!(!key.matches(&BundleKey::new(&resized, 1.0, links())));
+
+        // Collapsing shrinks the node to its header and moves the sockets onto it
+        let collapsed = socket_state(Size::new(50.0, 18.0), 1.0);
+        assert!(!key.matches(&BundleKey::new(&collapsed, 1.0, links())));
+    }
+
+    #[test]
+    fn bundle_key_changes_with_links() {
+        let state = socket_state(Size::new(50.0, 30.0), 1.0);
+        let key = BundleKey::new(&state, 1.0, links());
+
+        let mut with_waypoint = links();
+        with_waypoint[0].as_mut().unwrap().1 = true;
+        assert!(!key.matches(&BundleKey::new(&state, 1.0, with_waypoint)));
+        assert!(!key.matches(&BundleKey::new(&state, 1.0, vec![None])));
+    }
+}
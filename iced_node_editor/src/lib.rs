@@ -1,27 +1,42 @@
 #![allow(clippy::type_complexity)]
 
+mod bundling;
 mod connection;
+mod graph;
 mod graph_container;
 mod matrix;
 mod mesh_renderer;
 mod node;
 mod node_element;
+mod reroute;
+mod routing;
+mod stroke;
 pub mod styles;
 
 pub use matrix::Matrix;
 
 pub use connection::connection;
+pub use graph::find_cycles;
+pub use graph::would_create_cycle;
 pub use graph_container::graph_container;
 pub use node::node;
+pub use reroute::reroute;
 
 pub use connection::Connection;
+pub use connection::ConnectionStyle;
 pub use connection::Endpoint;
+pub use connection::Flow;
 pub use connection::Link;
 pub use connection::LogicalEndpoint;
+pub use connection::WidthMode;
 pub use graph_container::GraphContainer;
 pub use node::Node;
 pub use node::Socket;
 pub use node::SocketRole;
+pub use node::SocketShape;
 pub use node::SocketSide;
 pub use node_element::GraphNodeElement;
 pub use node_element::ScalableWidget;
+pub use reroute::Reroute;
+pub use stroke::LineCap;
+pub use stroke::LineJoin;
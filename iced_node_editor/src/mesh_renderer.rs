@@ -1,5 +1,6 @@
+use iced::advanced::graphics::color;
 use iced::advanced::graphics::mesh::{Indexed, SolidVertex2D, Renderer};
-use iced::{Point, Rectangle, Size, Transformation};
+use iced::{Color, Point, Rectangle, Size, Transformation};
 
 pub trait MeshRenderer {
     fn draw_buffers(&mut self, buffers: Indexed<SolidVertex2D>);
@@ -30,3 +31,50 @@ impl MeshRenderer for iced::Renderer {
         }
     }
 }
+
+/// Triangulates a convex polygon as a fan around its first point.
+pub(crate) fn fill_polygon(points: &[Point], color: Color) -> Indexed<SolidVertex2D> {
+    let color = color::pack(color);
+    let vertices = points
+        .iter()
+        .map(|p| SolidVertex2D {
+            position: [p.x, p.y],
+            color,
+        })
+        .collect();
+
+    let mut indices = Vec::new();
+    for i in 1..points.len().saturating_sub(1) as u32 {
+        indices.extend([0, i, i + 1]);
+    }
+
+    Indexed { vertices, indices }
+}
+
+/// Triangulates the area between two closed outlines with the same number of points.
+pub(crate) fn fill_between(
+    outer: &[Point],
+    inner: &[Point],
+    color: Color,
+) -> Indexed<SolidVertex2D> {
+    let color = color::pack(color);
+    let vertices = outer
+        .iter()
+        .zip(inner)
+        .flat_map(|(o, i)| [*o, *i])
+        .map(|p| SolidVertex2D {
+            position: [p.x, p.y],
+            color,
+        })
+        .collect();
+
+    let n = outer.len() as u32;
+    let mut indices = Vec::new();
+    for i in 0..n {
+        let (o1, i1) = (2 * i, 2 * i + 1);
+        let (o2, i2) = (2 * ((i + 1) % n), 2 * ((i + 1) % n) + 1);
+        indices.extend([o1, o2, i2, o1, i2, i1]);
+    }
+
+    Indexed { vertices, indices }
+}
@@ -1,12 +1,15 @@
 use iced::advanced::widget::Tree;
-use iced::advanced::{renderer, widget, Clipboard, Layout, Shell, Widget};
+use iced::advanced::{layout, overlay, renderer, widget, Clipboard, Layout, Shell, Widget};
+use iced::time::{Duration, Instant};
+use iced::window::RedrawRequest;
 use iced::{
-    alignment, event, mouse, Alignment, Background, Border, Color, Element, Event, Length, Padding,
-    Pixels, Point, Rectangle, Size, Vector,
+    alignment, event, mouse, window, Alignment, Background, Border, Color, Element, Event, Length,
+    Padding, Pixels, Point, Rectangle, Size, Vector,
 };
 
 use crate::{
-    node_element::{GraphNodeElement, ScalableWidget},
+    mesh_renderer::{fill_between, fill_polygon, MeshRenderer},
+    node_element::{GraphNodeElement, ScalableWidget, SocketLayout},
     styles::node::StyleSheet,
 };
 
@@ -22,12 +25,19 @@ where
     padding: Padding,
     style: Theme::Style,
     content: Element<'a, Message, Theme, Renderer>,
+    header: Option<Element<'a, Message, Theme, Renderer>>,
+    drag_by_header: bool,
+    collapsed: bool,
+    on_toggle_collapse: Option<Box<dyn Fn(bool) -> Message + 'a>>,
+    on_resize: Option<Box<dyn Fn(Size) -> Message + 'a>>,
+    min_size: Size,
     sockets: Vec<Socket<'a, Message, Theme, Renderer>>,
     socket_spacing: f32,
     position: Point,
     horizontal_alignment: alignment::Horizontal,
     vertical_alignment: alignment::Vertical,
     on_translate: Option<Box<dyn Fn((f32, f32)) -> Message + 'a>>,
+    tooltip_delay: Duration,
 }
 
 pub struct Socket<'a, Message, Theme, Renderer> {
@@ -37,6 +47,7 @@ pub struct Socket<'a, Message, Theme, Renderer> {
     pub max_height: f32,
 
     pub blob_side: SocketSide,
+    pub blob_shape: SocketShape,
     pub blob_radius: f32,
     pub blob_border_radius: f32,
     pub blob_color: Color,
@@ -44,28 +55,278 @@ pub struct Socket<'a, Message, Theme, Renderer> {
 
     pub content: Element<'a, Message, Theme, Renderer>,
     pub content_alignment: alignment::Horizontal,
+
+    /// An element shown next to the blob after the cursor has hovered over it for a while.
+    pub tooltip: Option<Element<'a, Message, Theme, Renderer>>,
 }
 
 impl<'a, Message, Theme, Renderer> Socket<'a, Message, Theme, Renderer> {
-    pub fn blob_rect(&self, node_left: f32, node_width: f32, center_y: f32) -> Rectangle {
-        let x = match self.blob_side {
-            SocketSide::Left => node_left,
-            SocketSide::Right => node_left + node_width,
+    /// Creates a socket with the given role and content. Input sockets are placed on the left
+    /// edge of the node and output sockets on the right; all other properties start out with
+    /// defaults that can be changed with the builder methods below or through the public fields.
+    pub fn new<T>(role: SocketRole, content: T) -> Self
+    where
+        T: Into<Element<'a, Message, Theme, Renderer>>,
+    {
+        Socket {
+            role,
+            min_height: 0.0,
+            max_height: f32::INFINITY,
+            blob_side: match role {
+                SocketRole::In => SocketSide::Left,
+                SocketRole::Out => SocketSide::Right,
+            },
+            blob_shape: SocketShape::Rounded,
+            blob_radius: 5.0,
+            blob_border_radius: 0.0,
+            blob_color: Color::WHITE,
+            blob_border_color: None,
+            content: content.into(),
+            content_alignment: match role {
+                SocketRole::In => alignment::Horizontal::Left,
+                SocketRole::Out => alignment::Horizontal::Right,
+            },
+            tooltip: None,
+        }
+    }
+
+    pub fn min_height(mut self, min_height: f32) -> Self {
+        self.min_height = min_height;
+        self
+    }
+
+    pub fn max_height(mut self, max_height: f32) -> Self {
+        self.max_height = max_height;
+        self
+    }
+
+    pub fn blob_side(mut self, blob_side: SocketSide) -> Self {
+        self.blob_side = blob_side;
+        self
+    }
+
+    pub fn blob_shape(mut self, blob_shape: SocketShape) -> Self {
+        self.blob_shape = blob_shape;
+        self
+    }
+
+    pub fn blob_radius(mut self, blob_radius: f32) -> Self {
+        self.blob_radius = blob_radius;
+        self
+    }
+
+    pub fn blob_border_radius(mut self, blob_border_radius: f32) -> Self {
+        self.blob_border_radius = blob_border_radius;
+        self
+    }
+
+    pub fn blob_color(mut self, blob_color: Color) -> Self {
+        self.blob_color = blob_color;
+        self
+    }
+
+    /// Sets the color of the blob's border. If `None`, the one from the style sheet is used.
+    pub fn blob_border_color(mut self, blob_border_color: Option<Color>) -> Self {
+        self.blob_border_color = blob_border_color;
+        self
+    }
+
+    pub fn content_alignment(mut self, content_alignment: alignment::Horizontal) -> Self {
+        self.content_alignment = content_alignment;
+        self
+    }
+
+    pub fn tooltip(mut self, tooltip: impl Into<Element<'a, Message, Theme, Renderer>>) -> Self {
+        self.tooltip = Some(tooltip.into());
+        self
+    }
+
+    /// Computes the rectangle of this socket's blob on the edge of a node with the given bounds.
+    /// `center` is the position of the blob's center along that edge, i.e. a y coordinate for
+    /// sockets on the left or right, and an x coordinate for sockets on the top or bottom.
+    pub fn blob_rect_on_edge(&self, node_bounds: Rectangle, center: f32) -> Rectangle {
+        let half_length = match self.blob_shape {
+            SocketShape::Pill => self.blob_radius * 2.0,
+            _ => self.blob_radius,
+        };
+        let (center, half_size) = match self.blob_side {
+            SocketSide::Left => (
+                Point::new(node_bounds.x, center),
+                Size::new(self.blob_radius, half_length),
+            ),
+            SocketSide::Right => (
+                Point::new(node_bounds.x + node_bounds.width, center),
+                Size::new(self.blob_radius, half_length),
+            ),
+            SocketSide::Top => (
+                Point::new(center, node_bounds.y),
+                Size::new(half_length, self.blob_radius),
+            ),
+            SocketSide::Bottom => (
+                Point::new(center, node_bounds.y + node_bounds.height),
+                Size::new(half_length, self.blob_radius),
+            ),
         };
         Rectangle::new(
-            Point::new(x - self.blob_radius, center_y - self.blob_radius),
-            Size::new(self.blob_radius * 2.0, self.blob_radius * 2.0),
+            Point::new(center.x - half_size.width, center.y - half_size.height),
+            half_size * 2.0,
         )
     }
+
+    /// Computes the rectangle of this socket's blob for a node spanning `node_width` from
+    /// `node_left`, with the blob centered at `center_y`. Sockets on the top or bottom edge are
+    /// treated as if the node had zero height at `center_y`, centered horizontally.
+    #[deprecated(note = "use `blob_rect_on_edge`, which supports sockets on all edges")]
+    pub fn blob_rect(&self, node_left: f32, node_width: f32, center_y: f32) -> Rectangle {
+        let node_bounds =
+            Rectangle::new(Point::new(node_left, center_y), Size::new(node_width, 0.0));
+        match self.blob_side {
+            SocketSide::Left | SocketSide::Right => self.blob_rect_on_edge(node_bounds, center_y),
+            SocketSide::Top | SocketSide::Bottom => {
+                self.blob_rect_on_edge(node_bounds, node_bounds.center_x())
+            }
+        }
+    }
+
+    /// Computes the blob rectangle from the bounds of the node and of this socket's content area.
+    fn blob_rect_in(&self, node_bounds: Rectangle, socket_bounds: Rectangle) -> Rectangle {
+        match self.blob_side {
+            SocketSide::Left | SocketSide::Right => {
+                self.blob_rect_on_edge(node_bounds, socket_bounds.center_y())
+            }
+            SocketSide::Top | SocketSide::Bottom => {
+                self.blob_rect_on_edge(node_bounds, socket_bounds.center_x())
+            }
+        }
+    }
+
+    /// The direction in which data flows through this socket's blob.
+    fn flow_direction(&self) -> Vector {
+        match self.role {
+            SocketRole::In => Vector::new(0.0, 0.0) - self.blob_side.normal(),
+            SocketRole::Out => self.blob_side.normal(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SocketSide {
     Left,
     Right,
+    Top,
+    Bottom,
 }
 
+impl SocketSide {
+    /// The side across from this one.
+    pub(crate) fn opposite(&self) -> SocketSide {
+        match self {
+            SocketSide::Left => SocketSide::Right,
+            SocketSide::Right => SocketSide::Left,
+            SocketSide::Top => SocketSide::Bottom,
+            SocketSide::Bottom => SocketSide::Top,
+        }
+    }
+
+    /// The unit vector pointing away from the node on this side.
+    pub(crate) fn normal(&self) -> Vector {
+        match self {
+            SocketSide::Left => Vector::new(-1.0, 0.0),
+            SocketSide::Right => Vector::new(1.0, 0.0),
+            SocketSide::Top => Vector::new(0.0, -1.0),
+            SocketSide::Bottom => Vector::new(0.0, 1.0),
+        }
+    }
+}
+
+/// The shape in which a socket's blob is drawn.
+///
+/// Apart from [`SocketShape::Rounded`], all shapes are drawn as meshes.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SocketShape {
+    /// A square with corners rounded by the socket's `blob_border_radius`.
+    Rounded,
+    Circle,
+    Square,
+    Diamond,
+    /// A triangle pointing in the direction data flows through the socket.
+    Triangle,
+    /// A hollow circle.
+    Ring,
+    /// A capsule twice as long along the node edge as it is wide, e.g. for sockets that accept
+    /// multiple links.
+    Pill,
+}
+
+/// The number of segments used to approximate a full circle when drawing blobs.
+const CIRCLE_SEGMENTS: usize = 24;
+
+/// The thickness of a [`SocketShape::Ring`], relative to its radius.
+const RING_THICKNESS: f32 = 0.4;
+
+impl SocketShape {
+    fn outline(self, rect: Rectangle, direction: Vector) -> Vec<Point> {
+        let center = rect.center();
+        let (half_width, half_height) = (rect.width / 2.0, rect.height / 2.0);
+
+        match self {
+            SocketShape::Rounded | SocketShape::Square => vec![
+                Point::new(rect.x, rect.y),
+                Point::new(rect.x + rect.width, rect.y),
+                Point::new(rect.x + rect.width, rect.y + rect.height),
+                Point::new(rect.x, rect.y + rect.height),
+            ],
+            SocketShape::Circle | SocketShape::Ring => (0..CIRCLE_SEGMENTS)
+                .map(|i| {
+                    let angle = i as f32 / CIRCLE_SEGMENTS as f32 * std::f32::consts::TAU;
+                    Point::new(
+                        center.x + angle.cos() * half_width,
+                        center.y + angle.sin() * half_height,
+                    )
+                })
+                .collect(),
+            SocketShape::Diamond => vec![
+                Point::new(center.x, rect.y),
+                Point::new(rect.x + rect.width, center.y),
+                Point::new(center.x, rect.y + rect.height),
+                Point::new(rect.x, center.y),
+            ],
+            SocketShape::Triangle => {
+                let forward = Vector::new(direction.x * half_width, direction.y * half_height);
+                let across = Vector::new(-direction.y * half_width, direction.x * half_height);
+                vec![
+                    center + forward,
+                    center - forward + across,
+                    center - forward - across,
+                ]
+            }
+            SocketShape::Pill => {
+                let radius = half_width.min(half_height);
+                let corners = [
+                    (rect.x + rect.width - radius, rect.y + rect.height - radius),
+                    (rect.x + radius, rect.y + rect.height - radius),
+                    (rect.x + radius, rect.y + radius),
+                    (rect.x + rect.width - radius, rect.y + radius),
+                ];
+                let segments_per_corner = CIRCLE_SEGMENTS / 4;
+
+                corners
+                    .iter()
+                    .enumerate()
+                    .flat_map(|(corner, (x, y))| {
+                        (0..=segments_per_corner).map(move |i| {
+                            let angle = (corner as f32 + i as f32 / segments_per_corner as f32)
+                                * std::f32::consts::FRAC_PI_2;
+                            Point::new(x + angle.cos() * radius, y + angle.sin() * radius)
+                        })
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum SocketRole {
     In,
     Out,
@@ -74,8 +335,55 @@ pub enum SocketRole {
 #[derive(Debug)]
 struct NodeState {
     drag_start_position: Option<Point>,
+    hovered_socket: Option<(usize, Instant)>,
+    tooltip_socket: Option<usize>,
+    resize: Option<Resize>,
+    /// The size of the content frame in graph units, and the scale, from the last layout.
+    frame_size: Size,
+    scale: f32,
+}
+
+/// A resize of a node in progress.
+#[derive(Debug, Clone, Copy)]
+struct Resize {
+    handle: ResizeHandle,
+    start_position: Point,
+    start_size: Size,
+}
+
+/// The parts of a node that can be dragged to resize it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ResizeHandle {
+    Right,
+    Bottom,
+    Corner,
 }
 
+impl ResizeHandle {
+    fn interaction(self) -> mouse::Interaction {
+        match self {
+            ResizeHandle::Right => mouse::Interaction::ResizingHorizontally,
+            ResizeHandle::Bottom => mouse::Interaction::ResizingVertically,
+            ResizeHandle::Corner => mouse::Interaction::ResizingDiagonallyDown,
+        }
+    }
+}
+
+/// How much a blob grows (relative to its radius) while the cursor hovers over it.
+const HOVERED_BLOB_GROWTH: f32 = 0.5;
+
+/// The distance between a blob and its tooltip.
+const TOOLTIP_GAP: f32 = 8.0;
+
+/// The space above and below the header of a node, in graph units.
+const HEADER_PADDING: f32 = 4.0;
+
+/// The size of the button in the header that collapses and expands a node, in graph units.
+const COLLAPSE_TOGGLE_SIZE: f32 = 10.0;
+
+/// The width of the areas along the right and bottom edges of a node that resize it, in pixels.
+const RESIZE_HANDLE_WIDTH: f32 = 6.0;
+
 impl<'a, Message, Theme, Renderer> Node<'a, Message, Theme, Renderer>
 where
     Theme: StyleSheet,
@@ -93,12 +401,19 @@ where
             padding: Padding::ZERO,
             style: Default::default(),
             content: content.into(),
+            header: None,
+            drag_by_header: false,
+            collapsed: false,
+            on_toggle_collapse: None,
+            on_resize: None,
+            min_size: Size::new(20.0, 20.0),
             sockets: vec![],
             socket_spacing: 0.0,
             position: Point::new(0.0, 0.0),
             horizontal_alignment: alignment::Horizontal::Left,
             vertical_alignment: alignment::Vertical::Top,
             on_translate: None,
+            tooltip_delay: Duration::from_millis(500),
         }
     }
 
@@ -165,6 +480,64 @@ where
         self
     }
 
+    /// Sets an element, e.g. a title, shown in a bar across the top of the node. The bar has the
+    /// header background from the style sheet.
+    pub fn header(mut self, header: impl Into<Element<'a, Message, Theme, Renderer>>) -> Self {
+        self.header = Some(header.into());
+        self
+    }
+
+    /// Makes only the header a handle for dragging the node around, rather than its whole body.
+    /// Has no effect on nodes without a header.
+    pub fn drag_by_header(mut self, drag_by_header: bool) -> Self {
+        self.drag_by_header = drag_by_header;
+        self
+    }
+
+    /// Collapses the node to its header, hiding its content and the content of its sockets. The
+    /// blobs of the sockets are stacked on the edges of the header, such that connections still
+    /// attach to them, but they cannot be dragged from or connected to until the node is expanded
+    /// again.
+    ///
+    /// Nodes without a header or a collapse toggle collapse to an empty bar, as high as one with
+    /// only the collapse toggle.
+    pub fn collapsed(mut self, collapsed: bool) -> Self {
+        self.collapsed = collapsed;
+        self
+    }
+
+    /// Shows a button in the header that collapses and expands the node. The closure receives
+    /// whether the node should be collapsed.
+    pub fn on_toggle_collapse<F>(mut self, f: F) -> Self
+    where
+        F: 'a + Fn(bool) -> Message,
+    {
+        self.on_toggle_collapse = Some(Box::new(f));
+        self
+    }
+
+    /// Lets users resize the node by dragging its right edge, its bottom edge or its bottom right
+    /// corner. The closure receives the new content size of the node in graph units, which is
+    /// meant to be passed to [`width`](Self::width) and [`height`](Self::height). Like these, it
+    /// is the size of the frame around the content, so the node's outer bounds are larger by the
+    /// header bar and by the bands of sockets above and below the content.
+    ///
+    /// The size is kept between [`min_size`](Self::min_size) and the maximum set by
+    /// [`max_width`](Self::max_width) and [`max_height`](Self::max_height).
+    pub fn on_resize<F>(mut self, f: F) -> Self
+    where
+        F: 'a + Fn(Size) -> Message,
+    {
+        self.on_resize = Some(Box::new(f));
+        self
+    }
+
+    /// Sets the smallest size, in graph units, that users can resize the node to.
+    pub fn min_size(mut self, min_size: Size) -> Self {
+        self.min_size = min_size;
+        self
+    }
+
     pub fn sockets(mut self, sockets: Vec<Socket<'a, Message, Theme, Renderer>>) -> Self {
         self.sockets = sockets;
         self
@@ -174,6 +547,246 @@ where
         self.socket_spacing = socket_spacing.into().0;
         self
     }
+
+    /// Sets how long the cursor has to hover over a socket blob before its tooltip is shown.
+    pub fn tooltip_delay(mut self, tooltip_delay: Duration) -> Self {
+        self.tooltip_delay = tooltip_delay;
+        self
+    }
+
+    fn blob_rects(&self, layout: Layout<'_>) -> Vec<Rectangle> {
+        let bounds = layout.bounds();
+
+        layout
+            .children()
+            .skip(1)
+            .zip(&self.sockets)
+            .map(|(socket_layout, socket)| socket.blob_rect_in(bounds, socket_layout.bounds()))
+            .collect()
+    }
+
+    fn layout_socket_content(
+        &self,
+        tree: &mut widget::Tree,
+        renderer: &Renderer,
+        socket_index: usize,
+        available_width: f32,
+    ) -> iced::advanced::layout::Node {
+        let socket = &self.sockets[socket_index];
+        let socket_limits = iced::advanced::layout::Limits::new(
+            Size {
+                width: 0.0,
+                height: socket.min_height,
+            },
+            Size {
+                width: available_width,
+                height: socket.max_height,
+            },
+        );
+
+        socket.content.as_widget().layout(
+            &mut tree.children[self.socket_tree_index(socket_index)],
+            renderer,
+            &socket_limits,
+        )
+    }
+
+    /// The header tree, if there is a header, is stored right after the tree of the node content.
+    fn header_tree_index(&self) -> usize {
+        1
+    }
+
+    /// Socket content trees are stored after the trees of the node content and the header.
+    fn socket_tree_index(&self, socket_index: usize) -> usize {
+        1 + usize::from(self.header.is_some()) + socket_index
+    }
+
+    /// Tooltip trees are stored after the trees of the socket contents, but only for sockets that
+    /// actually have a tooltip.
+    fn tooltip_tree_index(&self, socket_index: usize) -> Option<usize> {
+        self.sockets[socket_index].tooltip.as_ref()?;
+
+        let preceding_tooltips = self.sockets[..socket_index]
+            .iter()
+            .filter(|socket| socket.tooltip.is_some())
+            .count();
+        Some(self.socket_tree_index(self.sockets.len()) + preceding_tooltips)
+    }
+
+    /// Returns whether the node has a bar across its top, for its header or its collapse toggle.
+    fn has_header_bar(&self) -> bool {
+        self.header.is_some() || self.on_toggle_collapse.is_some()
+    }
+
+    /// Returns the layout of the header bar, which follows the layouts of the sockets.
+    fn header_layout<'l>(&self, layout: Layout<'l>) -> Option<Layout<'l>> {
+        if !self.has_header_bar() {
+            return None;
+        }
+        layout.children().nth(1 + self.sockets.len())
+    }
+
+    /// Returns the area that can be used to drag the node around.
+    fn drag_handle(&self, layout: Layout<'_>) -> Rectangle {
+        match self.header_layout(layout) {
+            Some(header_layout) if self.drag_by_header => header_layout.bounds(),
+            _ => layout.bounds(),
+        }
+    }
+
+    /// Returns the resize handle at the given position, if the node can be resized.
+    fn resize_handle_at(&self, layout: Layout<'_>, position: Point) -> Option<ResizeHandle> {
+        if self.on_resize.is_none() || self.collapsed {
+            return None;
+        }
+
+        // Blobs overlap the handles on the right and bottom edges, and presses on them start a
+        // connection instead
+        let bounds = layout.bounds();
+        if !bounds.contains(position)
+            || self
+                .blob_rects(layout)
+                .iter()
+                .any(|blob_rect| blob_rect.contains(position))
+        {
+            return None;
+        }
+        let right = position.x >= bounds.x + bounds.width - RESIZE_HANDLE_WIDTH;
+        let bottom = position.y >= bounds.y + bounds.height - RESIZE_HANDLE_WIDTH;
+        // The corner handle is larger, as it is the one used most
+        let corner = position.x >= bounds.x + bounds.width - RESIZE_HANDLE_WIDTH * 2.0
+            && position.y >= bounds.y + bounds.height - RESIZE_HANDLE_WIDTH * 2.0;
+
+        if corner {
+            Some(ResizeHandle::Corner)
+        } else if right {
+            Some(ResizeHandle::Right)
+        } else if bottom {
+            Some(ResizeHandle::Bottom)
+        } else {
+            None
+        }
+    }
+
+    /// Computes the size of the node after the given resize handle has been dragged by `delta`,
+    /// in graph units.
+    fn resized(&self, resize: Resize, delta: Vector) -> Size {
+        let mut size = resize.start_size;
+        if matches!(resize.handle, ResizeHandle::Right | ResizeHandle::Corner) {
+            size.width += delta.x;
+        }
+        if matches!(resize.handle, ResizeHandle::Bottom | ResizeHandle::Corner) {
+            size.height += delta.y;
+        }
+
+        Size::new(
+            size.width.min(self.max_width).max(self.min_size.width),
+            size.height.min(self.max_height).max(self.min_size.height),
+        )
+    }
+
+    /// Returns the bounds of the collapse toggle, which follows the header content in the layout
+    /// of the header bar.
+    fn collapse_toggle_bounds(&self, layout: Layout<'_>) -> Option<Rectangle> {
+        self.on_toggle_collapse.as_ref()?;
+        Some(self.header_layout(layout)?.children().last()?.bounds())
+    }
+
+    /// Lays out a collapsed node, which only consists of its header bar. The content and the
+    /// sockets get empty layouts, with the sockets stacked at the middle of their edges.
+    fn layout_collapsed(
+        &self,
+        header_node: Option<iced::advanced::layout::Node>,
+        size: Size,
+        padding: Padding,
+        scale: f32,
+        socket_state: &mut super::node_element::SocketLayoutState,
+    ) -> iced::advanced::layout::Node {
+        let node_bounds = Rectangle::new(Point::ORIGIN, size);
+        let position = Vector::new(self.position.x, self.position.y) * scale;
+
+        let mut children = vec![iced::advanced::layout::Node::new(Size::ZERO)
+            .move_to(Point::new(padding.left, size.height))];
+        let mut in_sockets: Vec<SocketLayout> = vec![];
+        let mut out_sockets: Vec<SocketLayout> = vec![];
+        for socket in &self.sockets {
+            let center = Point::new(size.width / 2.0, size.height / 2.0);
+            children.push(
+                iced::advanced::layout::Node::with_children(
+                    Size::ZERO,
+                    vec![iced::advanced::layout::Node::new(Size::ZERO)],
+                )
+                .move_to(center),
+            );
+
+            let center = match socket.blob_side {
+                SocketSide::Left | SocketSide::Right => center.y,
+                SocketSide::Top | SocketSide::Bottom => center.x,
+            };
+            let socket_layout = SocketLayout {
+                rect: socket.blob_rect_on_edge(node_bounds, center) + position,
+                side: socket.blob_side,
+                color: Some(socket.blob_color),
+                stacked: true,
+            };
+            match socket.role {
+                SocketRole::In => in_sockets.push(socket_layout),
+                SocketRole::Out => out_sockets.push(socket_layout),
+            }
+        }
+        children.extend(header_node);
+
+        socket_state.inputs.push(in_sockets);
+        socket_state.outputs.push(out_sockets);
+        socket_state.nodes.push(node_bounds + position);
+
+        iced::advanced::layout::Node::with_children(size, children).translate(position)
+    }
+
+    fn update_hovered_socket(
+        &self,
+        state: &mut NodeState,
+        event: &Event,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        shell: &mut Shell<'_, Message>,
+    ) {
+        // The blobs of a collapsed node are stacked, so none of them can be told apart
+        let hovered = cursor
+            .position()
+            .filter(|_| !self.collapsed)
+            .and_then(|cursor_position| {
+                self.blob_rects(layout)
+                    .iter()
+                    .position(|blob_rect| blob_rect.contains(cursor_position))
+            });
+
+        if hovered != state.hovered_socket.map(|(socket_index, _)| socket_index) {
+            if state.tooltip_socket.take().is_some() {
+                shell.invalidate_layout();
+            }
+
+            let now = Instant::now();
+            state.hovered_socket = hovered.map(|socket_index| (socket_index, now));
+            if hovered.is_some_and(|socket_index| self.sockets[socket_index].tooltip.is_some()) {
+                shell.request_redraw(RedrawRequest::At(now + self.tooltip_delay));
+            }
+        }
+
+        if let Event::Window(window::Event::RedrawRequested(now)) = event {
+            if let Some((socket_index, hovered_since)) = state.hovered_socket {
+                let show_at = hovered_since + self.tooltip_delay;
+                if state.tooltip_socket.is_none() && self.sockets[socket_index].tooltip.is_some() {
+                    if *now >= show_at {
+                        state.tooltip_socket = Some(socket_index);
+                        shell.invalidate_layout();
+                    } else {
+                        shell.request_redraw(RedrawRequest::At(show_at));
+                    }
+                }
+            }
+        }
+    }
 }
 
 pub fn node<'a, Message, Theme, Renderer>(
@@ -222,13 +835,144 @@ where
 
         let content_frame_size = limits.resolve(self.width, self.height, content.size());
 
+        let state = tree.state.downcast_mut::<NodeState>();
+        state.frame_size = content_frame_size;
+        state.scale = scale;
+
         let content_available_width =
             content_frame_size.width * scale - padding.left - padding.right;
         let content_available_height =
             content_frame_size.height * scale - padding.top - padding.bottom;
         let content_available_size = Size::new(content_available_width, content_available_height);
 
-        content.move_to_mut(Point::new(padding.left, padding.top));
+        let header_node = self.has_header_bar().then(|| {
+            // Leave room for the collapse toggle on the right
+            let toggle_width = if self.on_toggle_collapse.is_some() {
+                COLLAPSE_TOGGLE_SIZE + HEADER_PADDING
+            } else {
+                0.0
+            };
+            let mut header_content = self.header.as_ref().map(|header| {
+                header.as_widget().layout(
+                    &mut tree.children[self.header_tree_index()],
+                    renderer,
+                    &iced::advanced::layout::Limits::new(
+                        Size::ZERO,
+                        Size::new(
+                            content_frame_size.width - padding.left - padding.right - toggle_width,
+                            f32::INFINITY,
+                        ),
+                    ),
+                )
+            });
+
+            let content_height = header_content
+                .as_ref()
+                .map_or(0.0, |header_content| header_content.size().height);
+            let toggle_height = if toggle_width > 0.0 {
+                COLLAPSE_TOGGLE_SIZE
+            } else {
+                0.0
+            };
+            let header_size = Size::new(
+                content_frame_size.width * scale,
+                (content_height.max(toggle_height) + HEADER_PADDING * 2.0) * scale,
+            );
+            if let Some(header_content) = &mut header_content {
+                header_content.move_to_mut(Point::new(padding.left, 0.0));
+                header_content.align_mut(Alignment::Start, Alignment::Center, header_size);
+            }
+            let toggle = (toggle_width > 0.0).then(|| {
+                let size = COLLAPSE_TOGGLE_SIZE * scale;
+                iced::advanced::layout::Node::new(Size::new(size, size)).move_to(Point::new(
+                    header_size.width - (COLLAPSE_TOGGLE_SIZE + HEADER_PADDING) * scale,
+                    (header_size.height - size) / 2.0,
+                ))
+            });
+            iced::advanced::layout::Node::with_children(
+                header_size,
+                header_content.into_iter().chain(toggle).collect(),
+            )
+        });
+        let header_height = header_node
+            .as_ref()
+            .map_or(0.0, |header_node| header_node.size().height);
+
+        if self.collapsed {
+            // Without a header bar, the node collapses to an empty one
+            let height = if header_node.is_some() {
+                header_height
+            } else {
+                (COLLAPSE_TOGGLE_SIZE + HEADER_PADDING * 2.0) * scale
+            };
+            return self.layout_collapsed(
+                header_node,
+                Size::new(content_frame_size.width * scale, height),
+                padding,
+                scale,
+                socket_state,
+            );
+        }
+
+        let mut socket_nodes: Vec<Option<iced::advanced::layout::Node>> =
+            self.sockets.iter().map(|_| None).collect();
+        let mut socket_centers: Vec<f32> = vec![0.0; self.sockets.len()];
+
+        // Sockets on the top and bottom edges are laid out in bands above and below the content,
+        // evenly dividing the available width between them
+        let mut band_heights = [0.0_f32; 2];
+        for (band_index, side) in [SocketSide::Top, SocketSide::Bottom]
+            .into_iter()
+            .enumerate()
+        {
+            let band_sockets: Vec<usize> = (0..self.sockets.len())
+                .filter(|socket_index| self.sockets[*socket_index].blob_side == side)
+                .collect();
+            if band_sockets.is_empty() {
+                continue;
+            }
+
+            let slot_width = content_available_size.width / band_sockets.len() as f32;
+            let band_contents: Vec<_> = band_sockets
+                .iter()
+                .map(|socket_index| {
+                    self.layout_socket_content(tree, renderer, *socket_index, slot_width / scale)
+                })
+                .collect();
+            let band_height = band_contents
+                .iter()
+                .map(|socket_content| socket_content.size().height * scale)
+                .fold(0.0, f32::max);
+
+            for (slot_index, (socket_index, mut socket_content)) in
+                band_sockets.into_iter().zip(band_contents).enumerate()
+            {
+                let slot_size = Size::new(slot_width, band_height);
+                socket_content.align_mut(
+                    Alignment::from(self.sockets[socket_index].content_alignment),
+                    Alignment::Center,
+                    slot_size,
+                );
+
+                let mut socket_node =
+                    iced::advanced::layout::Node::with_children(slot_size, vec![socket_content]);
+                socket_node.move_to_mut(Point::new(
+                    padding.left + slot_index as f32 * slot_width,
+                    0.0,
+                ));
+                socket_nodes[socket_index] = Some(socket_node);
+                socket_centers[socket_index] =
+                    padding.left + (slot_index as f32 + 0.5) * slot_width;
+            }
+
+            band_heights[band_index] = self.socket_spacing * scale + band_height;
+        }
+        let [top_band_height, bottom_band_height] = band_heights;
+
+        content.move_to_mut(Point::new(
+            padding.left,
+            header_height + padding.top + top_band_height,
+        ));
         content.align_mut(
             Alignment::from(self.horizontal_alignment),
             Alignment::from(self.vertical_alignment),
@@ -237,31 +981,19 @@ where
 
         let mut children = vec![content];
 
-        let mut in_sockets: Vec<Rectangle> = vec![];
-        let mut out_sockets: Vec<Rectangle> = vec![];
-
-        let mut socket_top: f32 = content_available_size.height;
+        let mut socket_top: f32 = header_height + top_band_height + content_available_size.height;
         for (socket_index, socket) in self.sockets.iter().enumerate() {
-            socket_top += self.socket_spacing * scale;
-
-            let socket_content_available_width =
-                content_frame_size.width - padding.left - padding.right;
+            if matches!(socket.blob_side, SocketSide::Top | SocketSide::Bottom) {
+                continue;
+            }
 
-            let socket_limits = iced::advanced::layout::Limits::new(
-                Size {
-                    width: 0.0,
-                    height: socket.min_height,
-                },
-                Size {
-                    width: socket_content_available_width,
-                    height: socket.max_height,
-                },
-            );
+            socket_top += self.socket_spacing * scale;
 
-            let mut socket_content = socket.content.as_widget().layout(
-                &mut tree.children[socket_index + 1],
+            let mut socket_content = self.layout_socket_content(
+                tree,
                 renderer,
-                &socket_limits,
+                socket_index,
+                content_frame_size.width - padding.left - padding.right,
             );
 
             let socket_content_size_scaled = Size::new(
@@ -283,54 +1015,120 @@ where
                 vec![socket_content],
             );
             socket_node.move_to_mut(Point::new(self.padding.left, padding.top + socket_top));
+            socket_nodes[socket_index] = Some(socket_node);
+            socket_centers[socket_index] =
+                padding.top + socket_top + socket_area_size_scaled.height / 2.0;
+
+            socket_top += socket_content_size_scaled.height;
+        }
+
+        let total_size = Size::new(
+            content_frame_size.width * scale,
+            padding.top + socket_top + bottom_band_height + padding.bottom,
+        );
+        let node_bounds = Rectangle::new(Point::ORIGIN, total_size);
+
+        let mut in_sockets: Vec<SocketLayout> = vec![];
+        let mut out_sockets: Vec<SocketLayout> = vec![];
+
+        for (socket_index, socket) in self.sockets.iter().enumerate() {
+            let mut socket_node = socket_nodes[socket_index]
+                .take()
+                .expect("every socket should have been laid out");
+
+            // The bands are positioned now that the total size is known
+            match socket.blob_side {
+                SocketSide::Top => {
+                    socket_node.translate_mut(Vector::new(0.0, header_height + padding.top))
+                }
+                SocketSide::Bottom => socket_node.translate_mut(Vector::new(
+                    0.0,
+                    total_size.height
+                        - padding.bottom
+                        - (bottom_band_height - self.socket_spacing * scale),
+                )),
+                SocketSide::Left | SocketSide::Right => {}
+            }
             children.push(socket_node);
 
-            let blob_rect = socket.blob_rect(
-                0.0,
-                content_frame_size.width * scale,
-                padding.top + socket_top + socket_area_size_scaled.height / 2.0,
-            ) + (Vector::new(self.position.x, self.position.y) * scale);
+            let socket_layout = SocketLayout {
+                rect: socket.blob_rect_on_edge(node_bounds, socket_centers[socket_index])
+                    + (Vector::new(self.position.x, self.position.y) * scale),
+                side: socket.blob_side,
+                color: Some(socket.blob_color),
+                stacked: false,
+            };
             match socket.role {
-                SocketRole::In => in_sockets.push(blob_rect),
-                SocketRole::Out => out_sockets.push(blob_rect),
+                SocketRole::In => in_sockets.push(socket_layout),
+                SocketRole::Out => out_sockets.push(socket_layout),
             }
-
-            socket_top += socket_content_size_scaled.height;
         }
 
+        children.extend(header_node);
+
         socket_state.inputs.push(in_sockets);
         socket_state.outputs.push(out_sockets);
+        socket_state
+            .nodes
+            .push(node_bounds + Vector::new(self.position.x, self.position.y) * scale);
 
-        let total_size = Size::new(
-            content_frame_size.width * scale,
-            padding.top + socket_top + padding.bottom,
-        );
         let node = iced::advanced::layout::Node::with_children(total_size, children);
 
         node.translate(Vector::new(self.position.x, self.position.y) * scale)
     }
+
+    fn dismiss_tooltip(&self, tree: &mut widget::Tree) -> bool {
+        let state = tree.state.downcast_mut::<NodeState>();
+        state.hovered_socket = None;
+        state.tooltip_socket.take().is_some()
+    }
+
+    fn is_dragged(&self, tree: &widget::Tree) -> bool {
+        let state = tree.state.downcast_ref::<NodeState>();
+        state.drag_start_position.is_some() || state.resize.is_some()
+    }
 }
 
 impl<'a, Message, Theme, Renderer> Widget<Message, Theme, Renderer>
     for Node<'a, Message, Theme, Renderer>
 where
     Theme: StyleSheet,
-    Renderer: renderer::Renderer,
+    Renderer: renderer::Renderer + MeshRenderer,
 {
     fn children(&self) -> Vec<widget::Tree> {
         let mut res = vec![widget::Tree::new(&self.content)];
+        if let Some(header) = &self.header {
+            res.push(widget::Tree::new(header));
+        }
         for socket in &self.sockets {
             res.push(widget::Tree::new(&socket.content));
         }
+        for tooltip in self
+            .sockets
+            .iter()
+            .filter_map(|socket| socket.tooltip.as_ref())
+        {
+            res.push(widget::Tree::new(tooltip));
+        }
         res
     }
 
     fn diff(&self, tree: &mut widget::Tree) {
         let mut new_children: Vec<&dyn Widget<Message, Theme, Renderer>> =
             vec![self.content.as_widget()];
+        if let Some(header) = &self.header {
+            new_children.push(header.as_widget());
+        }
         for socket in &self.sockets {
             new_children.push(socket.content.as_widget());
         }
+        for tooltip in self
+            .sockets
+            .iter()
+            .filter_map(|socket| socket.tooltip.as_ref())
+        {
+            new_children.push(tooltip.as_widget());
+        }
         tree.diff_children(new_children.as_slice())
     }
 
@@ -341,6 +1139,11 @@ where
     fn state(&self) -> widget::tree::State {
         widget::tree::State::new(NodeState {
             drag_start_position: None,
+            hovered_socket: None,
+            tooltip_socket: None,
+            resize: None,
+            frame_size: Size::ZERO,
+            scale: 1.0,
         })
     }
 
@@ -383,13 +1186,73 @@ where
             );
         }
 
+        if let Some(header_layout) = self.header_layout(layout) {
+            let header_bounds = header_layout.bounds();
+            if let Some(header_background) = style.header_background {
+                // Inset the bar by the border, such that it does not cover it
+                let inset = style.border_width;
+                renderer.fill_quad(
+                    renderer::Quad {
+                        bounds: Rectangle {
+                            x: header_bounds.x + inset,
+                            y: header_bounds.y + inset,
+                            width: (header_bounds.width - inset * 2.0).max(0.0),
+                            height: (header_bounds.height
+                                - if self.collapsed { inset * 2.0 } else { inset })
+                            .max(0.0),
+                        },
+                        border: Border {
+                            // A collapsed node consists of nothing but the header bar
+                            radius: if self.collapsed {
+                                iced::border::radius((style.border_radius - inset).max(0.0))
+                            } else {
+                                iced::border::top((style.border_radius - inset).max(0.0))
+                            },
+                            ..Border::default()
+                        },
+                        ..renderer::Quad::default()
+                    },
+                    header_background,
+                );
+            }
+
+            if let Some((header, header_content_layout)) =
+                self.header.as_ref().zip(header_layout.children().next())
+            {
+                // Only draw the header content if it would be sufficiently big
+                if header_bounds.width > header_content_layout.bounds().width
+                    && header_bounds.height * 2.0 > header_content_layout.bounds().height
+                {
+                    header.as_widget().draw(
+                        &tree.children[self.header_tree_index()],
+                        renderer,
+                        theme,
+                        &renderer::Style {
+                            text_color: style.text_color.unwrap_or(renderer_style.text_color),
+                        },
+                        header_content_layout,
+                        cursor,
+                        viewport,
+                    );
+                }
+            }
+
+            if let Some(toggle_bounds) = self.collapse_toggle_bounds(layout) {
+                renderer.draw_buffers(fill_polygon(
+                    &collapse_toggle_outline(toggle_bounds, self.collapsed),
+                    style.text_color.unwrap_or(renderer_style.text_color),
+                ));
+            }
+        }
+
         let mut children_iter = layout.children();
         let content_layout = children_iter
             .next()
             .expect("there should be a layout node for the graph node content");
 
         // Only draw node content if it would be sufficiently big
-        if layout.bounds().width > content_layout.bounds().width
+        if !self.collapsed
+            && layout.bounds().width > content_layout.bounds().width
             && layout.bounds().height > content_layout.bounds().height
         {
             self.content.as_widget().draw(
@@ -405,7 +1268,7 @@ where
             );
         }
 
-        for (socket_index, socket_layout) in children_iter.enumerate() {
+        for (socket_index, socket_layout) in children_iter.take(self.sockets.len()).enumerate() {
             let socket = &self.sockets[socket_index];
 
             let child_layout = socket_layout
@@ -414,11 +1277,12 @@ where
                 .expect("the socket layout node should have one child");
 
             // Only draw socket content if it would be sufficiently big
-            if socket_layout.bounds().width > child_layout.bounds().width
+            if !self.collapsed
+                && socket_layout.bounds().width > child_layout.bounds().width
                 && (socket_layout.bounds().height * 2.0) > child_layout.bounds().height
             {
                 socket.content.as_widget().draw(
-                    &tree.children[socket_index + 1],
+                    &tree.children[self.socket_tree_index(socket_index)],
                     renderer,
                     theme,
                     &renderer::Style {
@@ -430,20 +1294,17 @@ where
                 );
             }
 
-            // Draw blob
-            let blob_rect =
-                socket.blob_rect(bounds.x, bounds.width, socket_layout.bounds().center_y());
-            renderer.fill_quad(
-                renderer::Quad {
-                    bounds: blob_rect,
-                    border: Border {
-                        color: socket.blob_border_color.unwrap_or(style.border_color),
-                        width: style.border_width,
-                        radius: socket.blob_border_radius.into(),
-                    },
-                    ..renderer::Quad::default()
-                },
-                Background::Color(socket.blob_color),
+            // Draw blob, slightly enlarged while hovered, unless it is stacked with the others
+            let mut blob_rect = socket.blob_rect_in(bounds, socket_layout.bounds());
+            if !self.collapsed && cursor.is_over(blob_rect) {
+                blob_rect = blob_rect.expand(socket.blob_radius * HOVERED_BLOB_GROWTH);
+            }
+            draw_blob(
+                renderer,
+                socket,
+                blob_rect,
+                socket.blob_border_color.unwrap_or(style.border_color),
+                style.border_width,
             );
         }
     }
@@ -460,13 +1321,51 @@ where
         viewport: &Rectangle<f32>,
     ) -> event::Status {
         let mut status = event::Status::Ignored;
+        let header_tree_index = self.header_tree_index();
         let state = tree.state.downcast_mut::<NodeState>();
 
+        self.update_hovered_socket(state, &event, layout, cursor, shell);
+
+        if let Some(resize) = state.resize {
+            match event {
+                Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)) => {
+                    state.resize = None;
+                    // The graph container holds back edge bundling until drags end
+                    shell.invalidate_layout();
+                    return event::Status::Captured;
+                }
+                Event::Mouse(mouse::Event::CursorMoved { position }) => {
+                    let delta = (position - resize.start_position) * (1.0 / state.scale);
+                    if let Some(f) = &self.on_resize {
+                        shell.publish(f(self.resized(resize, delta)));
+                    }
+                    return event::Status::Captured;
+                }
+                _ => {}
+            }
+        } else if let (
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)),
+            Some(cursor_position),
+        ) = (&event, cursor.position())
+        {
+            // Resize handles lie on top of the content, such that they can always be reached
+            if let Some(handle) = self.resize_handle_at(layout, cursor_position) {
+                state.resize = Some(Resize {
+                    handle,
+                    start_position: cursor_position,
+                    start_size: state.frame_size,
+                });
+                return event::Status::Captured;
+            }
+        }
+
         if let Some(cursor_position) = cursor.position() {
             if let Some(start) = state.drag_start_position {
                 match event {
                     Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)) => {
                         state.drag_start_position = None;
+                        // The graph container holds back edge bundling until drags end
+                        shell.invalidate_layout();
                     }
                     Event::Mouse(mouse::Event::CursorMoved { .. }) => {
                         let delta = cursor_position - start;
@@ -479,44 +1378,83 @@ where
                     }
                     _ => {}
                 }
+            } else if matches!(
+                event,
+                Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left))
+            ) && self
+                .collapse_toggle_bounds(layout)
+                .is_some_and(|bounds| bounds.contains(cursor_position))
+            {
+                if let Some(f) = &self.on_toggle_collapse {
+                    shell.publish(f(!self.collapsed));
+                }
+                status = event::Status::Captured;
             } else {
                 let mut layout_children_iter = layout.children();
                 let content_layout = layout_children_iter
                     .next()
                     .expect("there should be a layout node for the graph node content");
 
-                status = self.content.as_widget_mut().on_event(
-                    &mut tree.children[0],
-                    event.clone(),
-                    content_layout,
-                    cursor,
-                    renderer,
-                    clipboard,
-                    shell,
-                    viewport,
-                );
-
-                for (socket_index, socket_layout) in layout_children_iter.enumerate() {
-                    if status == event::Status::Captured {
-                        break;
-                    }
-
-                    status = self.sockets[socket_index].content.as_widget_mut().on_event(
-                        &mut tree.children[socket_index + 1],
+                // The content of collapsed nodes is hidden, and thus does not receive events
+                if !self.collapsed {
+                    status = self.content.as_widget_mut().on_event(
+                        &mut tree.children[0],
                         event.clone(),
-                        socket_layout,
+                        content_layout,
                         cursor,
                         renderer,
                         clipboard,
                         shell,
                         viewport,
                     );
+
+                    for (socket_index, socket_layout) in
+                        layout_children_iter.take(self.sockets.len()).enumerate()
+                    {
+                        if status == event::Status::Captured {
+                            break;
+                        }
+
+                        let tree_index = self.socket_tree_index(socket_index);
+                        status = self.sockets[socket_index].content.as_widget_mut().on_event(
+                            &mut tree.children[tree_index],
+                            event.clone(),
+                            socket_layout
+                                .children()
+                                .next()
+                                .expect("the socket layout node should have one child"),
+                            cursor,
+                            renderer,
+                            clipboard,
+                            shell,
+                            viewport,
+                        );
+                    }
+                }
+
+                let header_layout = self.header_layout(layout);
+                if let Some((header, header_layout)) = self.header.as_mut().zip(header_layout) {
+                    if status == event::Status::Ignored {
+                        status = header.as_widget_mut().on_event(
+                            &mut tree.children[header_tree_index],
+                            event.clone(),
+                            header_layout.children().next().expect(
+                                "the header layout node should have a child for its content",
+                            ),
+                            cursor,
+                            renderer,
+                            clipboard,
+                            shell,
+                            viewport,
+                        );
+                    }
                 }
             }
         }
 
+        let drag_handle = self.drag_handle(layout);
         if let Some(cursor_position) = cursor.position() {
-            if status == event::Status::Ignored && layout.bounds().contains(cursor_position) {
+            if status == event::Status::Ignored && drag_handle.contains(cursor_position) {
                 if let Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) = event {
                     state.drag_start_position = Some(cursor_position);
                     status = event::Status::Captured;
@@ -527,6 +1465,27 @@ where
         status
     }
 
+    fn overlay<'b>(
+        &'b mut self,
+        tree: &'b mut widget::Tree,
+        layout: Layout<'_>,
+        _renderer: &Renderer,
+        translation: Vector,
+    ) -> Option<overlay::Element<'b, Message, Theme, Renderer>> {
+        let state = tree.state.downcast_ref::<NodeState>();
+        let socket_index = state.tooltip_socket?;
+        let tree_index = self.tooltip_tree_index(socket_index)?;
+        let anchor = *self.blob_rects(layout).get(socket_index)?;
+
+        let socket = &self.sockets[socket_index];
+        Some(overlay::Element::new(Box::new(SocketTooltip {
+            tooltip: socket.tooltip.as_ref()?,
+            tree: &mut tree.children[tree_index],
+            anchor: anchor + translation,
+            side: socket.blob_side,
+        })))
+    }
+
     fn mouse_interaction(
         &self,
         tree: &widget::Tree,
@@ -535,13 +1494,99 @@ where
         viewport: &Rectangle,
         renderer: &Renderer,
     ) -> mouse::Interaction {
-        self.content.as_widget().mouse_interaction(
-            &tree.children[0],
-            layout,
-            cursor,
-            viewport,
-            renderer,
-        )
+        let state = tree.state.downcast_ref::<NodeState>();
+        if let Some(resize) = state.resize {
+            return resize.handle.interaction();
+        }
+        if let Some(handle) = cursor
+            .position()
+            .and_then(|position| self.resize_handle_at(layout, position))
+        {
+            return handle.interaction();
+        }
+
+        if state.drag_start_position.is_some() {
+            return mouse::Interaction::Grabbing;
+        }
+
+        // Presses on blobs start a connection, which is handled by the graph container
+        if !self.collapsed
+            && self
+                .blob_rects(layout)
+                .iter()
+                .any(|blob_rect| cursor.is_over(*blob_rect))
+        {
+            return mouse::Interaction::Crosshair;
+        }
+
+        if self
+            .collapse_toggle_bounds(layout)
+            .is_some_and(|bounds| cursor.is_over(bounds))
+        {
+            return mouse::Interaction::Pointer;
+        }
+
+        let mut children_iter = layout.children();
+        let content_layout = children_iter
+            .next()
+            .expect("there should be a layout node for the graph node content");
+        let mut interaction = mouse::Interaction::default();
+        if !self.collapsed {
+            interaction = self.content.as_widget().mouse_interaction(
+                &tree.children[0],
+                content_layout,
+                cursor,
+                viewport,
+                renderer,
+            );
+
+            for (socket_index, socket_layout) in children_iter.take(self.sockets.len()).enumerate()
+            {
+                if interaction != mouse::Interaction::default() {
+                    break;
+                }
+
+                interaction = self.sockets[socket_index]
+                    .content
+                    .as_widget()
+                    .mouse_interaction(
+                        &tree.children[self.socket_tree_index(socket_index)],
+                        socket_layout
+                            .children()
+                            .next()
+                            .expect("the socket layout node should have one child"),
+                        cursor,
+                        viewport,
+                        renderer,
+                    );
+            }
+        }
+
+        if interaction == mouse::Interaction::default() {
+            if let Some((header, header_layout)) = self.header.as_ref().zip(
+                self.header_layout(layout)
+                    .and_then(|header_layout| header_layout.children().next()),
+            ) {
+                interaction = header.as_widget().mouse_interaction(
+                    &tree.children[self.header_tree_index()],
+                    header_layout,
+                    cursor,
+                    viewport,
+                    renderer,
+                );
+            }
+        }
+
+        if interaction != mouse::Interaction::default() {
+            return interaction;
+        }
+
+        let drag_handle = self.drag_handle(layout);
+        if self.on_translate.is_some() && cursor.is_over(drag_handle) {
+            mouse::Interaction::Grab
+        } else {
+            mouse::Interaction::default()
+        }
     }
 
     fn size(&self) -> Size<Length> {
@@ -552,12 +1597,149 @@ where
     }
 }
 
+/// Returns the triangle drawn as the collapse toggle, pointing down while the node is expanded and
+/// to the right while it is collapsed.
+fn collapse_toggle_outline(bounds: Rectangle, collapsed: bool) -> [Point; 3] {
+    if collapsed {
+        [
+            Point::new(bounds.x, bounds.y),
+            Point::new(bounds.x + bounds.width, bounds.center_y()),
+            Point::new(bounds.x, bounds.y + bounds.height),
+        ]
+    } else {
+        [
+            Point::new(bounds.x, bounds.y),
+            Point::new(bounds.x + bounds.width, bounds.y),
+            Point::new(bounds.center_x(), bounds.y + bounds.height),
+        ]
+    }
+}
+
+fn draw_blob<Message, Theme, Renderer>(
+    renderer: &mut Renderer,
+    socket: &Socket<'_, Message, Theme, Renderer>,
+    blob_rect: Rectangle,
+    border_color: Color,
+    border_width: f32,
+) where
+    Renderer: renderer::Renderer + MeshRenderer,
+{
+    let direction = socket.flow_direction();
+
+    match socket.blob_shape {
+        SocketShape::Rounded => {
+            renderer.fill_quad(
+                renderer::Quad {
+                    bounds: blob_rect,
+                    border: Border {
+                        color: border_color,
+                        width: border_width,
+                        radius: socket.blob_border_radius.into(),
+                    },
+                    ..renderer::Quad::default()
+                },
+                Background::Color(socket.blob_color),
+            );
+        }
+        SocketShape::Ring => {
+            let thickness = blob_rect.width.min(blob_rect.height) / 2.0 * RING_THICKNESS;
+            renderer.draw_buffers(fill_between(
+                &SocketShape::Ring.outline(blob_rect, direction),
+                &SocketShape::Ring.outline(blob_rect.shrink(thickness), direction),
+                socket.blob_color,
+            ));
+        }
+        shape => {
+            // The border is drawn as the full shape, with the fill on top of it
+            let fill_rect = if border_width > 0.0 {
+                renderer.draw_buffers(fill_polygon(
+                    &shape.outline(blob_rect, direction),
+                    border_color,
+                ));
+                blob_rect.shrink(border_width)
+            } else {
+                blob_rect
+            };
+            renderer.draw_buffers(fill_polygon(
+                &shape.outline(fill_rect, direction),
+                socket.blob_color,
+            ));
+        }
+    }
+}
+
+struct SocketTooltip<'a, 'b, Message, Theme, Renderer> {
+    tooltip: &'b Element<'a, Message, Theme, Renderer>,
+    tree: &'b mut widget::Tree,
+    anchor: Rectangle,
+    side: SocketSide,
+}
+
+impl<'a, 'b, Message, Theme, Renderer> overlay::Overlay<Message, Theme, Renderer>
+    for SocketTooltip<'a, 'b, Message, Theme, Renderer>
+where
+    Renderer: renderer::Renderer,
+{
+    fn layout(&mut self, renderer: &Renderer, bounds: Size) -> layout::Node {
+        let node = self.tooltip.as_widget().layout(
+            self.tree,
+            renderer,
+            &layout::Limits::new(Size::ZERO, bounds),
+        );
+        let size = node.size();
+
+        // Place the tooltip on the outer side of the blob, but keep it within the viewport
+        let (x, y) = match self.side {
+            SocketSide::Left => (
+                self.anchor.x - TOOLTIP_GAP - size.width,
+                self.anchor.center_y() - size.height / 2.0,
+            ),
+            SocketSide::Right => (
+                self.anchor.x + self.anchor.width + TOOLTIP_GAP,
+                self.anchor.center_y() - size.height / 2.0,
+            ),
+            SocketSide::Top => (
+                self.anchor.center_x() - size.width / 2.0,
+                self.anchor.y - TOOLTIP_GAP - size.height,
+            ),
+            SocketSide::Bottom => (
+                self.anchor.center_x() - size.width / 2.0,
+                self.anchor.y + self.anchor.height + TOOLTIP_GAP,
+            ),
+        };
+
+        node.move_to(Point::new(
+            x.clamp(0.0, (bounds.width - size.width).max(0.0)),
+            y.clamp(0.0, (bounds.height - size.height).max(0.0)),
+        ))
+    }
+
+    fn draw(
+        &self,
+        renderer: &mut Renderer,
+        theme: &Theme,
+        style: &renderer::Style,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+    ) {
+        self.tooltip.as_widget().draw(
+            self.tree,
+            renderer,
+            theme,
+            style,
+            layout,
+            cursor,
+            &layout.bounds(),
+        );
+    }
+}
+
 impl<'a, Message, Theme, Renderer> From<Node<'a, Message, Theme, Renderer>>
     for GraphNodeElement<'a, Message, Theme, Renderer>
 where
     Message: 'a,
     Theme: StyleSheet + 'a,
-    Renderer: renderer::Renderer + 'a,
+    Renderer: renderer::Renderer + MeshRenderer + 'a,
 {
     fn from(node: Node<'a, Message, Theme, Renderer>) -> Self {
         Self::new(node)
@@ -1,7 +1,10 @@
 use iced::advanced::widget::Tree;
 use iced::advanced::{layout, renderer, Widget};
-use iced::Rectangle;
+use iced::{Color, Point, Rectangle, Vector};
 use std::borrow::Borrow;
+use std::collections::HashMap;
+
+use crate::{Link, SocketSide};
 
 pub struct GraphNodeElement<'a, Message, Theme, Renderer> {
     widget: Box<dyn GraphWidget<'a, Message, Theme, Renderer> + 'a>,
@@ -45,21 +48,129 @@ where
         scale: f32,
         socket_state: &mut SocketLayoutState,
     ) -> layout::Node;
+
+    /// Returns the link drawn by this element, if it is a connection.
+    fn link(&self) -> Option<&Link> {
+        None
+    }
+
+    /// Returns the points, in graph space, that the path drawn by this element passes through.
+    fn waypoints(&self) -> &[Point] {
+        &[]
+    }
+
+    /// Returns whether this element is being dragged or resized, in which case its mouse
+    /// interaction takes precedence over that of the elements on top of it.
+    fn is_dragged(&self, _tree: &Tree) -> bool {
+        false
+    }
+
+    /// Hides the tooltip shown by this element, if any. Returns whether one was hidden.
+    fn dismiss_tooltip(&self, _tree: &mut Tree) -> bool {
+        false
+    }
+
+    /// Returns whether this element is animated, and thus needs to be redrawn every frame.
+    fn is_animated(&self) -> bool {
+        false
+    }
+
+    /// Returns the distance from `point`, given in the coordinate space of the sockets, to the
+    /// path drawn by this element, together with the index of the path's leg closest to it. This
+    /// is also the index at which a waypoint at `point` would be inserted.
+    fn path_distance(&self, _point: Point) -> Option<(f32, usize)> {
+        None
+    }
+
+    /// Returns the path this element draws when it is not bundled, in the coordinate space of the
+    /// sockets, if it may be bundled with the paths of other elements.
+    fn bundleable_path(
+        &self,
+        _scale: f32,
+        _socket_state: &SocketLayoutState,
+    ) -> Option<Vec<Vector>> {
+        None
+    }
 }
 
 #[derive(Debug)]
 pub struct SocketLayoutState {
-    pub(crate) inputs: Vec<Vec<Rectangle>>,
-    pub(crate) outputs: Vec<Vec<Rectangle>>,
+    pub(crate) inputs: Vec<Vec<SocketLayout>>,
+    pub(crate) outputs: Vec<Vec<SocketLayout>>,
+    /// The bounds of every node, in the same coordinate space as the socket rects.
+    pub(crate) nodes: Vec<Rectangle>,
+    /// For every unordered pair of nodes, the number of connections between them and how many of
+    /// those have been laid out so far.
+    pub(crate) parallel_edges: HashMap<(usize, usize), (usize, usize)>,
+    /// The distance in graph units between connections that link the same pair of nodes.
+    pub(crate) parallel_spacing: f32,
+    /// Whether the element laid out next is the dangling connection, which is not counted among,
+    /// and not spread apart from, the connections between the same nodes.
+    pub(crate) dangling: bool,
+    /// The bundled path, in the coordinate space of the sockets, that the next connection laid out
+    /// follows instead of its own.
+    pub(crate) bundled_path: Option<Vec<Vector>>,
     pub(crate) done: bool,
 }
 
+/// The position of a socket's blob, as determined while laying out its node.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct SocketLayout {
+    pub(crate) rect: Rectangle,
+    pub(crate) side: SocketSide,
+    pub(crate) color: Option<Color>,
+    /// Whether the blob is stacked onto the blobs of the other sockets on the same edge, as its
+    /// node is collapsed. Stacked blobs still anchor connections, but cannot be pressed.
+    pub(crate) stacked: bool,
+}
+
 impl SocketLayoutState {
     pub fn clear(&mut self) {
         self.inputs.clear();
         self.outputs.clear();
+        self.nodes.clear();
+        self.parallel_edges.clear();
+        self.dangling = false;
+        self.bundled_path = None;
         self.done = false;
     }
+
+    /// Counts the connections between every pair of nodes, before they are laid out.
+    pub(crate) fn count_parallel_edges<'l>(&mut self, links: impl Iterator<Item = &'l Link>) {
+        for (first, second) in links.filter_map(Link::node_pair) {
+            let key = (first.min(second), first.max(second));
+            self.parallel_edges.entry(key).or_default().0 += 1;
+        }
+    }
+
+    /// Returns the distance in graph units by which the connection with the given link is pushed
+    /// aside, such that it does not overlap other connections between the same nodes. Must be
+    /// called once per connection, in the order they are laid out.
+    pub(crate) fn parallel_offset(&mut self, link: &Link) -> f32 {
+        if self.dangling {
+            return 0.0;
+        }
+        let Some((first, second)) = link.node_pair() else {
+            return 0.0;
+        };
+        let Some((count, seen)) = self
+            .parallel_edges
+            .get_mut(&(first.min(second), first.max(second)))
+        else {
+            return 0.0;
+        };
+
+        let index = *seen;
+        *seen += 1;
+        let offset = (index as f32 - (*count as f32 - 1.0) / 2.0) * self.parallel_spacing;
+        // Offsets are relative to the direction from the lower to the higher node index, so that
+        // connections in opposite directions are spread apart, too
+        if first > second {
+            -offset
+        } else {
+            offset
+        }
+    }
 }
 
 impl<'a, Message, Theme, Renderer> GraphNodeElement<'a, Message, Theme, Renderer>
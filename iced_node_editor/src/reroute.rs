@@ -0,0 +1,334 @@
+use iced::advanced::widget::Tree;
+use iced::advanced::{layout, renderer, widget, Clipboard, Layout, Shell, Widget};
+use iced::{
+    event, mouse, Background, Border, Color, Event, Length, Point, Rectangle, Size, Vector,
+};
+
+use crate::{
+    node_element::{GraphNodeElement, ScalableWidget, SocketLayout},
+    styles::node::StyleSheet,
+    SocketSide,
+};
+
+/// A point that connections can be routed through, with one input and one output socket and no
+/// body (also known as a knot). It is part of the graph content like a [`Node`](crate::Node),
+/// and has to be placed before the connections, too.
+///
+/// Its input socket is the left half of the dot and its output socket the right half, unless
+/// configured otherwise with [`Reroute::input_side`]. The ring around the dot can be used to drag
+/// the reroute around.
+pub struct Reroute<'a, Message, Theme>
+where
+    Theme: StyleSheet,
+{
+    position: Point,
+    radius: f32,
+    color: Option<Color>,
+    input_side: SocketSide,
+    style: Theme::Style,
+    on_translate: Option<Box<dyn Fn((f32, f32)) -> Message + 'a>>,
+}
+
+#[derive(Debug)]
+struct RerouteState {
+    drag_start_position: Option<Point>,
+}
+
+/// The width of the ring around the dot that can be used to drag a reroute, in graph units.
+const DRAG_RING_WIDTH: f32 = 5.0;
+
+impl<'a, Message, Theme> Reroute<'a, Message, Theme>
+where
+    Theme: StyleSheet,
+{
+    pub fn new(position: Point) -> Self {
+        Reroute {
+            position,
+            radius: 5.0,
+            color: None,
+            input_side: SocketSide::Left,
+            style: Default::default(),
+            on_translate: None,
+        }
+    }
+
+    pub fn on_translate<F>(mut self, f: F) -> Self
+    where
+        F: 'a + Fn((f32, f32)) -> Message,
+    {
+        self.on_translate = Some(Box::new(f));
+        self
+    }
+
+    /// Sets the radius of the dot, in graph units.
+    pub fn radius(mut self, radius: f32) -> Self {
+        self.radius = radius;
+        self
+    }
+
+    /// Sets the color of the dot. If `None`, the border color from the style sheet is used.
+    pub fn color(mut self, color: Option<Color>) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Sets the side of the dot that its input socket is on, e.g. [`SocketSide::Top`] for graphs
+    /// that flow from top to bottom. The output socket is on the opposite side.
+    pub fn input_side(mut self, input_side: SocketSide) -> Self {
+        self.input_side = input_side;
+        self
+    }
+
+    pub fn style(mut self, style: impl Into<Theme::Style>) -> Self {
+        self.style = style.into();
+        self
+    }
+}
+
+pub fn reroute<'a, Message, Theme>(position: Point) -> Reroute<'a, Message, Theme>
+where
+    Theme: StyleSheet,
+{
+    Reroute::new(position)
+}
+
+impl<'a, Message, Theme, Renderer> ScalableWidget<Message, Renderer> for Reroute<'a, Message, Theme>
+where
+    Theme: StyleSheet,
+    Renderer: renderer::Renderer,
+{
+    fn layout(
+        &self,
+        _tree: &mut Tree,
+        _renderer: &Renderer,
+        _limits: &layout::Limits,
+        scale: f32,
+        socket_state: &mut super::node_element::SocketLayoutState,
+    ) -> layout::Node {
+        if socket_state.done {
+            panic!("the graph content must consist of nodes, then connections; it is not allowed to have (more) nodes after the connections");
+        }
+
+        let center = Point::new(self.position.x * scale, self.position.y * scale);
+        let radius = self.radius * scale;
+        let outer_radius = (self.radius + DRAG_RING_WIDTH) * scale;
+        let bounds = Rectangle::new(
+            center - Vector::new(outer_radius, outer_radius),
+            Size::new(outer_radius * 2.0, outer_radius * 2.0),
+        );
+
+        let output_side = self.input_side.opposite();
+        socket_state.inputs.push(vec![SocketLayout {
+            rect: half_dot(center, radius, self.input_side),
+            side: self.input_side,
+            color: self.color,
+            stacked: false,
+        }]);
+        socket_state.outputs.push(vec![SocketLayout {
+            rect: half_dot(center, radius, output_side),
+            side: output_side,
+            color: self.color,
+            stacked: false,
+        }]);
+        socket_state.nodes.push(bounds);
+
+        layout::Node::new(bounds.size()).move_to(bounds.position())
+    }
+
+    fn is_dragged(&self, tree: &Tree) -> bool {
+        tree.state
+            .downcast_ref::<RerouteState>()
+            .drag_start_position
+            .is_some()
+    }
+}
+
+impl<'a, Message, Theme, Renderer> Widget<Message, Theme, Renderer> for Reroute<'a, Message, Theme>
+where
+    Theme: StyleSheet,
+    Renderer: renderer::Renderer,
+{
+    fn tag(&self) -> widget::tree::Tag {
+        widget::tree::Tag::of::<RerouteState>()
+    }
+
+    fn state(&self) -> widget::tree::State {
+        widget::tree::State::new(RerouteState {
+            drag_start_position: None,
+        })
+    }
+
+    fn layout(
+        &self,
+        _tree: &mut Tree,
+        _renderer: &Renderer,
+        _limits: &layout::Limits,
+    ) -> layout::Node {
+        todo!("This should never be called.")
+    }
+
+    fn draw(
+        &self,
+        tree: &Tree,
+        renderer: &mut Renderer,
+        theme: &Theme,
+        _renderer_style: &renderer::Style,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        _viewport: &Rectangle,
+    ) {
+        let style = theme.appearance(&self.style);
+        let state = tree.state.downcast_ref::<RerouteState>();
+        let bounds = layout.bounds();
+
+        // Show the drag ring while it is hovered or in use
+        if state.drag_start_position.is_some() || self.is_over_ring(bounds, cursor) {
+            renderer.fill_quad(
+                renderer::Quad {
+                    bounds,
+                    border: Border {
+                        color: style.border_color,
+                        width: style.border_width.max(1.0),
+                        radius: (bounds.width / 2.0).into(),
+                    },
+                    ..renderer::Quad::default()
+                },
+                Background::Color(Color::TRANSPARENT),
+            );
+        }
+
+        let ring_width = bounds.width / 2.0 * DRAG_RING_WIDTH / (self.radius + DRAG_RING_WIDTH);
+        let dot = bounds.shrink(ring_width);
+        renderer.fill_quad(
+            renderer::Quad {
+                bounds: dot,
+                border: Border {
+                    color: Color::TRANSPARENT,
+                    width: 0.0,
+                    radius: (dot.width / 2.0).into(),
+                },
+                ..renderer::Quad::default()
+            },
+            Background::Color(self.color.unwrap_or(style.border_color)),
+        );
+    }
+
+    fn on_event(
+        &mut self,
+        tree: &mut Tree,
+        event: Event,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        _renderer: &Renderer,
+        _clipboard: &mut dyn Clipboard,
+        shell: &mut Shell<'_, Message>,
+        _viewport: &Rectangle,
+    ) -> event::Status {
+        let state = tree.state.downcast_mut::<RerouteState>();
+        let Some(cursor_position) = cursor.position() else {
+            return event::Status::Ignored;
+        };
+
+        if let Some(start) = state.drag_start_position {
+            match event {
+                Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)) => {
+                    state.drag_start_position = None;
+                    // The graph container holds back edge bundling until drags end
+                    shell.invalidate_layout();
+                }
+                Event::Mouse(mouse::Event::CursorMoved { .. }) => {
+                    let delta = cursor_position - start;
+                    state.drag_start_position = Some(cursor_position);
+                    if let Some(f) = &self.on_translate {
+                        shell.publish(f((delta.x, delta.y)));
+                    }
+                    return event::Status::Captured;
+                }
+                _ => {}
+            }
+        } else if let Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) = event {
+            // Presses on the dot itself are handled by the graph container, as it is a socket
+            if self.is_over_ring(layout.bounds(), cursor) {
+                state.drag_start_position = Some(cursor_position);
+                return event::Status::Captured;
+            }
+        }
+
+        event::Status::Ignored
+    }
+
+    fn mouse_interaction(
+        &self,
+        tree: &Tree,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        _viewport: &Rectangle,
+        _renderer: &Renderer,
+    ) -> mouse::Interaction {
+        let state = tree.state.downcast_ref::<RerouteState>();
+        if state.drag_start_position.is_some() {
+            mouse::Interaction::Grabbing
+        } else if self.is_over_ring(layout.bounds(), cursor) {
+            mouse::Interaction::Grab
+        } else {
+            mouse::Interaction::default()
+        }
+    }
+
+    fn size(&self) -> Size<Length> {
+        Size {
+            width: Length::Shrink,
+            height: Length::Shrink,
+        }
+    }
+}
+
+impl<'a, Message, Theme> Reroute<'a, Message, Theme>
+where
+    Theme: StyleSheet,
+{
+    /// Returns whether the cursor is over the drag ring around the dot, given the bounds of the
+    /// whole reroute.
+    fn is_over_ring(&self, bounds: Rectangle, cursor: mouse::Cursor) -> bool {
+        let outer_radius = bounds.width / 2.0;
+        let dot_radius = outer_radius * self.radius / (self.radius + DRAG_RING_WIDTH);
+        cursor.position().is_some_and(|position| {
+            let distance = position.distance(bounds.center());
+            distance > dot_radius && distance <= outer_radius
+        })
+    }
+}
+
+/// Returns the half of a dot with the given center and radius that faces `side`.
+fn half_dot(center: Point, radius: f32, side: SocketSide) -> Rectangle {
+    match side {
+        SocketSide::Left => Rectangle::new(
+            center - Vector::new(radius, radius),
+            Size::new(radius, radius * 2.0),
+        ),
+        SocketSide::Right => Rectangle::new(
+            center - Vector::new(0.0, radius),
+            Size::new(radius, radius * 2.0),
+        ),
+        SocketSide::Top => Rectangle::new(
+            center - Vector::new(radius, radius),
+            Size::new(radius * 2.0, radius),
+        ),
+        SocketSide::Bottom => Rectangle::new(
+            center - Vector::new(radius, 0.0),
+            Size::new(radius * 2.0, radius),
+        ),
+    }
+}
+
+impl<'a, Message, Theme, Renderer> From<Reroute<'a, Message, Theme>>
+    for GraphNodeElement<'a, Message, Theme, Renderer>
+where
+    Message: 'a,
+    Theme: StyleSheet + 'a,
+    Renderer: renderer::Renderer + 'a,
+{
+    fn from(reroute: Reroute<'a, Message, Theme>) -> Self {
+        Self::new(reroute)
+    }
+}
@@ -0,0 +1,474 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use iced::{Rectangle, Vector};
+
+use crate::connection::ResolvedEndpoint;
+
+/// The cost of a bend in an orthogonal route, in multiples of the margin around obstacles.
+const BEND_COST: f32 = 2.0;
+
+/// The maximum number of obstacles a single route is planned around. The routing grid grows
+/// quadratically with the number of obstacles and routes are recomputed on every layout, so in
+/// dense graphs, only the obstacles closest to the endpoints are avoided.
+const MAX_OBSTACLES: usize = 12;
+
+/// The number of segments used to approximate a quarter circle when rounding corners.
+const CORNER_SEGMENTS: usize = 6;
+
+/// Routes a path consisting of horizontal and vertical segments from `from` to `to`, leaving and
+/// entering along the endpoint normals and going around the given obstacles at a distance of
+/// `margin`. If no such path exists, the obstacles are ignored.
+pub(crate) fn route_orthogonal(
+    from: ResolvedEndpoint,
+    to: ResolvedEndpoint,
+    obstacles: &[Rectangle],
+    margin: f32,
+) -> Vec<Vector> {
+    let start = from.position + from.normal * margin;
+    let end = to.position + to.normal * margin;
+    let start_direction = Direction::of(from.normal);
+    let end_direction = Direction::of(Vector::new(-to.normal.x, -to.normal.y));
+
+    let obstacles = nearby_obstacles(start, end, obstacles, margin);
+    let route = find_route(
+        start,
+        start_direction,
+        end,
+        end_direction,
+        &obstacles,
+        margin,
+    )
+    .unwrap_or_else(|| vec![start, Vector::new(end.x, start.y), end]);
+
+    let mut points = vec![from.position];
+    points.extend(route);
+    points.push(to.position);
+    remove_collinear(points)
+}
+
+/// Replaces every corner of a polyline by a circular arc of at most the given radius.
+pub(crate) fn round_corners(points: &[Vector], radius: f32) -> Vec<Vector> {
+    if points.len() < 3 || radius <= 0.0 {
+        return points.to_vec();
+    }
+
+    let mut rounded = vec![points[0]];
+    for window in points.windows(3) {
+        let [previous, corner, next] = [window[0], window[1], window[2]];
+        let incoming = corner - previous;
+        let outgoing = next - corner;
+        let incoming_length = length(incoming);
+        let outgoing_length = length(outgoing);
+        if incoming_length == 0.0 || outgoing_length == 0.0 {
+            rounded.push(corner);
+            continue;
+        }
+
+        // Don't let arcs overlap with the arcs of neighbouring corners
+        let corner_radius = radius.min(incoming_length / 2.0).min(outgoing_length / 2.0);
+        let arc_start = corner - incoming * (corner_radius / incoming_length);
+        let arc_end = corner + outgoing * (corner_radius / outgoing_length);
+
+        // A quadratic Bézier curve closely approximates the arc for right angles
+        for i in 0..=CORNER_SEGMENTS {
+            let t = i as f32 / CORNER_SEGMENTS as f32;
+            rounded.push(
+                arc_start * ((1.0 - t) * (1.0 - t))
+                    + corner * (2.0 * (1.0 - t) * t)
+                    + arc_end * (t * t),
+            );
+        }
+    }
+    rounded.push(points[points.len() - 1]);
+
+    rounded
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    Right,
+    Left,
+    Down,
+    Up,
+}
+
+impl Direction {
+    const ALL: [Direction; 4] = [
+        Direction::Right,
+        Direction::Left,
+        Direction::Down,
+        Direction::Up,
+    ];
+
+    /// Returns the axis-aligned direction closest to the given vector.
+    fn of(vector: Vector) -> Self {
+        if vector.x.abs() >= vector.y.abs() {
+            if vector.x >= 0.0 {
+                Direction::Right
+            } else {
+                Direction::Left
+            }
+        } else if vector.y >= 0.0 {
+            Direction::Down
+        } else {
+            Direction::Up
+        }
+    }
+
+    fn is_opposite(self, other: Direction) -> bool {
+        matches!(
+            (self, other),
+            (Direction::Right, Direction::Left)
+                | (Direction::Left, Direction::Right)
+                | (Direction::Down, Direction::Up)
+                | (Direction::Up, Direction::Down)
+        )
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// Collects the obstacles that may be relevant to a route between `start` and `end`: those near
+/// the area spanned by the two points, and transitively those near the collected obstacles. The
+/// obstacles closest to the area are collected first, and at most [`MAX_OBSTACLES`] of them.
+fn nearby_obstacles(
+    start: Vector,
+    end: Vector,
+    obstacles: &[Rectangle],
+    margin: f32,
+) -> Vec<Rectangle> {
+    let span = Rectangle::new(
+        iced::Point::new(start.x.min(end.x), start.y.min(end.y)),
+        iced::Size::new((start.x - end.x).abs(), (start.y - end.y).abs()),
+    );
+    let mut area = span.expand(margin);
+    let mut included = vec![false; obstacles.len()];
+    let mut nearby = Vec::new();
+
+    while nearby.len() < MAX_OBSTACLES {
+        let closest = obstacles
+            .iter()
+            .enumerate()
+            .filter(|(index, obstacle)| !included[*index] && area.intersects(obstacle))
+            .min_by(|(_, a), (_, b)| {
+                distance_between(span, **a).total_cmp(&distance_between(span, **b))
+            });
+        let Some((index, obstacle)) = closest else {
+            break;
+        };
+
+        included[index] = true;
+        area = area.union(&obstacle.expand(margin * 2.0));
+        nearby.push(*obstacle);
+    }
+
+    nearby
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct QueueEntry {
+    cost: f32,
+    node: usize,
+    direction: Direction,
+}
+
+impl Eq for QueueEntry {}
+
+impl Ord for QueueEntry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Reversed, such that the binary heap yields the cheapest entry first
+        other.cost.total_cmp(&self.cost)
+    }
+}
+
+impl PartialOrd for QueueEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Finds the cheapest route on a grid made of the lines around the obstacles and through the
+/// endpoints, where every bend adds to the cost.
+fn find_route(
+    start: Vector,
+    start_direction: Direction,
+    end: Vector,
+    end_direction: Direction,
+    obstacles: &[Rectangle],
+    margin: f32,
+) -> Option<Vec<Vector>> {
+    let mut xs = vec![start.x, end.x, (start.x + end.x) / 2.0];
+    let mut ys = vec![start.y, end.y, (start.y + end.y) / 2.0];
+    for obstacle in obstacles {
+        xs.extend([obstacle.x - margin, obstacle.x + obstacle.width + margin]);
+        ys.extend([obstacle.y - margin, obstacle.y + obstacle.height + margin]);
+    }
+    let xs = sorted_unique(xs);
+    let ys = sorted_unique(ys);
+
+    // Routes may pass closer than `margin` to obstacles, but not through them
+    let blocked: Vec<Rectangle> = obstacles
+        .iter()
+        .map(|obstacle| obstacle.expand(margin / 2.0))
+        .collect();
+    let is_free =
+        |a: Vector, b: Vector| !blocked.iter().any(|rect| segment_intersects(a, b, *rect));
+
+    let position = |node: usize| Vector::new(xs[node % xs.len()], ys[node / xs.len()]);
+    let neighbour = |node: usize, direction: Direction| {
+        let (x, y) = (node % xs.len(), node / xs.len());
+        match direction {
+            Direction::Right if x + 1 < xs.len() => Some(node + 1),
+            Direction::Left if x > 0 => Some(node - 1),
+            Direction::Down if y + 1 < ys.len() => Some(node + xs.len()),
+            Direction::Up if y > 0 => Some(node - xs.len()),
+            _ => None,
+        }
+    };
+    let find_node = |point: Vector| {
+        let x = xs.iter().position(|x| (x - point.x).abs() < f32::EPSILON)?;
+        let y = ys.iter().position(|y| (y - point.y).abs() < f32::EPSILON)?;
+        Some(y * xs.len() + x)
+    };
+
+    let start_node = find_node(start)?;
+    let end_node = find_node(end)?;
+    let bend_cost = BEND_COST * margin;
+
+    let state_count = xs.len() * ys.len() * Direction::ALL.len();
+    let mut costs = vec![f32::INFINITY; state_count];
+    let mut previous: Vec<Option<usize>> = vec![None; state_count];
+    let state = |node: usize, direction: Direction| node * Direction::ALL.len() + direction.index();
+
+    let mut queue = BinaryHeap::new();
+    costs[state(start_node, start_direction)] = 0.0;
+    queue.push(QueueEntry {
+        cost: 0.0,
+        node: start_node,
+        direction: start_direction,
+    });
+
+    let mut best_end: Option<(f32, usize)> = None;
+    while let Some(QueueEntry {
+        cost,
+        node,
+        direction,
+    }) = queue.pop()
+    {
+        if cost > costs[state(node, direction)] {
+            continue;
+        }
+        if best_end.is_some_and(|(best_cost, _)| cost >= best_cost) {
+            break;
+        }
+
+        if node == end_node {
+            let final_cost = cost
+                + match direction {
+                    d if d == end_direction => 0.0,
+                    d if d.is_opposite(end_direction) => 2.0 * bend_cost,
+                    _ => bend_cost,
+                };
+            if best_end.is_none_or(|(best_cost, _)| final_cost < best_cost) {
+                best_end = Some((final_cost, state(node, direction)));
+            }
+        }
+
+        for next_direction in Direction::ALL {
+            if next_direction.is_opposite(direction) {
+                continue;
+            }
+            let Some(next) = neighbour(node, next_direction) else {
+                continue;
+            };
+            if !is_free(position(node), position(next)) {
+                continue;
+            }
+
+            let next_cost = cost
+                + length(position(next) - position(node))
+                + if next_direction == direction {
+                    0.0
+                } else {
+                    bend_cost
+                };
+            let next_state = state(next, next_direction);
+            if next_cost < costs[next_state] {
+                costs[next_state] = next_cost;
+                previous[next_state] = Some(state(node, direction));
+                queue.push(QueueEntry {
+                    cost: next_cost,
+                    node: next,
+                    direction: next_direction,
+                });
+            }
+        }
+    }
+
+    let (_, mut current) = best_end?;
+    let mut route = vec![position(current / Direction::ALL.len())];
+    while let Some(before) = previous[current] {
+        route.push(position(before / Direction::ALL.len()));
+        current = before;
+    }
+    route.reverse();
+
+    Some(route)
+}
+
+fn sorted_unique(mut values: Vec<f32>) -> Vec<f32> {
+    values.sort_by(f32::total_cmp);
+    values.dedup_by(|a, b| (*a - *b).abs() < f32::EPSILON);
+    values
+}
+
+/// Returns whether an axis-aligned segment passes through the interior of a rectangle.
+fn segment_intersects(a: Vector, b: Vector, rect: Rectangle) -> bool {
+    a.x.max(b.x) > rect.x
+        && a.x.min(b.x) < rect.x + rect.width
+        && a.y.max(b.y) > rect.y
+        && a.y.min(b.y) < rect.y + rect.height
+}
+
+fn remove_collinear(points: Vec<Vector>) -> Vec<Vector> {
+    let mut result: Vec<Vector> = Vec::with_capacity(points.len());
+
+    for point in points {
+        if result
+            .last()
+            .is_some_and(|last| length(point - *last) < f32::EPSILON)
+        {
+            continue;
+        }
+
+        if let [.., before, last] = result[..] {
+            let cross =
+                (last.x - before.x) * (point.y - last.y) - (last.y - before.y) * (point.x - last.x);
+            if cross.abs() < f32::EPSILON {
+                result.pop();
+            }
+        }
+        result.push(point);
+    }
+
+    result
+}
+
+/// Returns the distance between the closest points of two rectangles, or zero if they overlap.
+fn distance_between(a: Rectangle, b: Rectangle) -> f32 {
+    let dx = (a.x - (b.x + b.width)).max(b.x - (a.x + a.width)).max(0.0);
+    let dy = (a.y - (b.y + b.height))
+        .max(b.y - (a.y + a.height))
+        .max(0.0);
+    (dx * dx + dy * dy).sqrt()
+}
+
+fn length(vector: Vector) -> f32 {
+    (vector.x * vector.x + vector.y * vector.y).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use iced::{Point, Size};
+
+    use super::*;
+
+    fn endpoint(x: f32, y: f32, normal_x: f32) -> ResolvedEndpoint {
+        ResolvedEndpoint {
+            position: Vector::new(x, y),
+            normal: Vector::new(normal_x, 0.0),
+            color: None,
+        }
+    }
+
+    fn is_orthogonal(points: &[Vector]) -> bool {
+        points
+            .windows(2)
+            .all(|pair| pair[0].x == pair[1].x || pair[0].y == pair[1].y)
+    }
+
+    #[test]
+    fn route_without_obstacles_is_direct() {
+        let route = route_orthogonal(
+            endpoint(0.0, 0.0, 1.0),
+            endpoint(100.0, 0.0, -1.0),
+            &[],
+            5.0,
+        );
+
+        assert_eq!(route, vec![Vector::new(0.0, 0.0), Vector::new(100.0, 0.0)]);
+    }
+
+    #[test]
+    fn route_goes_around_obstacle() {
+        let obstacle = Rectangle::new(Point::new(40.0, -20.0), Size::new(20.0, 40.0));
+        let route = route_orthogonal(
+            endpoint(0.0, 0.0, 1.0),
+            endpoint(100.0, 0.0, -1.0),
+            &[obstacle],
+            5.0,
+        );
+
+        assert!(is_orthogonal(&route));
+        assert_eq!(route.first(), Some(&Vector::new(0.0, 0.0)));
+        assert_eq!(route.last(), Some(&Vector::new(100.0, 0.0)));
+        assert!(!route
+            .windows(2)
+            .any(|pair| segment_intersects(pair[0], pair[1], obstacle)));
+    }
+
+    #[test]
+    fn nearby_obstacles_are_capped_and_closest_first() {
+        // A long chain of touching obstacles, all transitively near the route
+        let obstacles: Vec<Rectangle> = (0..100)
+            .map(|i| Rectangle::new(Point::new(i as f32 * 10.0, 2.0), Size::new(10.0, 10.0)))
+            .collect();
+        let nearby = nearby_obstacles(
+            Vector::new(0.0, 0.0),
+            Vector::new(20.0, 0.0),
+            &obstacles,
+            5.0,
+        );
+
+        assert_eq!(nearby.len(), MAX_OBSTACLES);
+        assert!(nearby.iter().all(|obstacle| obstacle.x < 200.0));
+    }
+
+    #[test]
+    fn round_corners_keeps_endpoints() {
+        let points = [
+            Vector::new(0.0, 0.0),
+            Vector::new(10.0, 0.0),
+            Vector::new(10.0, 10.0),
+        ];
+        let rounded = round_corners(&points, 4.0);
+
+        assert_eq!(rounded.first(), Some(&points[0]));
+        assert_eq!(rounded.last(), Some(&points[2]));
+        assert_eq!(rounded.len(), CORNER_SEGMENTS + 3);
+        assert_eq!(round_corners(&points, 0.0), points.to_vec());
+    }
+
+    #[test]
+    fn collinear_and_duplicate_points_are_removed() {
+        let points = vec![
+            Vector::new(0.0, 0.0),
+            Vector::new(5.0, 0.0),
+            Vector::new(5.0, 0.0),
+            Vector::new(10.0, 0.0),
+            Vector::new(10.0, 10.0),
+        ];
+
+        assert_eq!(
+            remove_collinear(points),
+            vec![
+                Vector::new(0.0, 0.0),
+                Vector::new(10.0, 0.0),
+                Vector::new(10.0, 10.0)
+            ]
+        );
+    }
+}
@@ -0,0 +1,466 @@
+use iced::Vector;
+
+/// The width in pixels of the fringe around strokes whose opacity fades out, which makes their
+/// edges look smooth without multisampling.
+const FEATHER: f32 = 1.0;
+
+/// The maximum length of a miter, relative to half the width of a stroke. Sharper corners are
+/// beveled instead.
+const MITER_LIMIT: f32 = 4.0;
+
+/// The smallest angle (in radians) between two segments for which a round join is generated.
+/// Gentler bends, e.g. along sampled curves, are mitered, which looks the same.
+const ROUND_JOIN_MIN_ANGLE: f32 = 0.3;
+
+/// The maximum angle (in radians) covered by one segment of a round join or cap.
+const ROUND_SEGMENT_ANGLE: f32 = 0.4;
+
+/// How the segments of a stroke are joined at bends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineJoin {
+    /// Extends the outer edges until they meet, beveling corners that are too sharp.
+    Miter,
+    /// Rounds off bends with a circular arc.
+    #[default]
+    Round,
+}
+
+/// How the ends of a stroke are drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineCap {
+    /// Ends the stroke exactly at its end points.
+    Butt,
+    /// Extends the stroke by a half circle at its end points.
+    #[default]
+    Round,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct StrokeVertex {
+    pub(crate) position: Vector,
+    /// The distance of the vertex along the stroked path.
+    pub(crate) distance: f32,
+    /// The opacity of the vertex, which is zero at the outer edge of the anti-aliasing fringe.
+    pub(crate) alpha: f32,
+}
+
+/// A cross-section through a stroke, where `left` and `right` are the offsets to its edges for a
+/// half width of 1.
+#[derive(Debug, Clone, Copy)]
+struct Section {
+    center: Vector,
+    left: Vector,
+    right: Vector,
+    distance: f32,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct Stroke {
+    pub(crate) vertices: Vec<StrokeVertex>,
+    pub(crate) indices: Vec<u32>,
+}
+
+impl Stroke {
+    fn push(&mut self, position: Vector, distance: f32, alpha: f32) -> u32 {
+        self.vertices.push(StrokeVertex {
+            position,
+            distance,
+            alpha,
+        });
+        self.vertices.len() as u32 - 1
+    }
+
+    /// Appends another stroke or fill to this one.
+    pub(crate) fn extend(&mut self, other: Stroke) {
+        let offset = self.vertices.len() as u32;
+        self.vertices.extend(other.vertices);
+        self.indices
+            .extend(other.indices.into_iter().map(|index| index + offset));
+    }
+}
+
+/// Tessellates a polyline of the given half width, with an anti-aliasing fringe around it.
+/// `start_distance` is the distance along the path at which the polyline starts.
+pub(crate) fn stroke(
+    points: &[Vector],
+    half_width: f32,
+    start_distance: f32,
+    join: LineJoin,
+    cap: LineCap,
+) -> Stroke {
+    let mut stroke = Stroke::default();
+
+    let mut points = points.to_vec();
+    points.dedup_by(|a, b| length(*a - *b) < f32::EPSILON);
+    if points.len() < 2 {
+        return stroke;
+    }
+
+    // Strokes thinner than the fringe fade out instead of getting thinner
+    let core = (half_width - FEATHER / 2.0).max(0.0);
+    let alpha = (half_width * 2.0 / FEATHER).min(1.0);
+
+    let mut sections = Vec::new();
+    let mut distance = start_distance;
+    for (i, point) in points.iter().enumerate() {
+        if i > 0 {
+            distance += length(*point - points[i - 1]);
+        }
+
+        let incoming = (i > 0).then(|| normalize(*point - points[i - 1]));
+        let outgoing = (i + 1 < points.len()).then(|| normalize(points[i + 1] - *point));
+        let section = |left: Vector, right: Vector| Section {
+            center: *point,
+            left,
+            right,
+            distance,
+        };
+
+        match (incoming, outgoing) {
+            (Some(direction), None) | (None, Some(direction)) => {
+                let normal = normal(direction);
+                sections.push(section(normal, -normal));
+            }
+            (Some(incoming), Some(outgoing)) => {
+                join_sections(incoming, outgoing, join, &mut |left, right| {
+                    sections.push(section(left, right))
+                });
+            }
+            (None, None) => unreachable!("strokes have at least two distinct points"),
+        }
+    }
+
+    for section in &sections {
+        let base = stroke.vertices.len() as u32;
+        stroke.push(
+            section.center + section.left * (core + FEATHER),
+            section.distance,
+            0.0,
+        );
+        stroke.push(
+            section.center + section.left * core,
+            section.distance,
+            alpha,
+        );
+        stroke.push(
+            section.center + section.right * core,
+            section.distance,
+            alpha,
+        );
+        stroke.push(
+            section.center + section.right * (core + FEATHER),
+            section.distance,
+            0.0,
+        );
+
+        if base > 0 {
+            let previous = base - 4;
+            for column in 0..3 {
+                stroke.indices.extend([
+                    previous + column,
+                    base + column,
+                    base + column + 1,
+                    previous + column,
+                    base + column + 1,
+                    previous + column + 1,
+                ]);
+            }
+        }
+    }
+
+    if cap == LineCap::Round {
+        let first = sections[0];
+        let last = sections[sections.len() - 1];
+        round_cap(&mut stroke, first, -1.0, core, alpha);
+        round_cap(&mut stroke, last, 1.0, core, alpha);
+    }
+
+    stroke
+}
+
+/// Fills a convex polygon, with an anti-aliasing fringe around it.
+pub(crate) fn fill(points: &[Vector], distance: f32) -> Stroke {
+    let mut fill = Stroke::default();
+    if points.len() < 3 {
+        return fill;
+    }
+
+    let center = points
+        .iter()
+        .fold(Vector::new(0.0, 0.0), |sum, point| sum + *point)
+        * (1.0 / points.len() as f32);
+    let center_index = fill.push(center, distance, 1.0);
+    for point in points {
+        fill.push(*point, distance, 1.0);
+    }
+    let count = points.len() as u32;
+    for i in 0..count {
+        fill.indices
+            .extend([center_index, 1 + i, 1 + (i + 1) % count]);
+    }
+
+    // Surround the polygon with a fringe that fades out from its outline. The fringe only
+    // extends outwards, such that translucent fills are not blended twice along their edges.
+    let orientation = (0..points.len())
+        .map(|i| cross(points[i], points[(i + 1) % points.len()]))
+        .sum::<f32>()
+        .signum();
+    let edge_normal = |i: usize| {
+        let edge = points[(i + 1) % points.len()] - points[i];
+        normal(normalize(edge)) * -orientation
+    };
+    let first_outer = fill.vertices.len() as u32;
+    for i in 0..points.len() {
+        let before = edge_normal((i + points.len() - 1) % points.len());
+        let after = edge_normal(i);
+        let miter = normalize(before + after);
+        let miter_length = (1.0 / dot(miter, after).max(f32::EPSILON)).min(MITER_LIMIT);
+        fill.push(points[i] + miter * (miter_length * FEATHER), distance, 0.0);
+    }
+    for i in 0..count {
+        let next = (i + 1) % count;
+        fill.indices.extend([
+            1 + i,
+            first_outer + i,
+            first_outer + next,
+            1 + i,
+            first_outer + next,
+            1 + next,
+        ]);
+    }
+
+    fill
+}
+
+/// Generates the cross-sections at a bend from `incoming` to `outgoing`.
+fn join_sections(
+    incoming: Vector,
+    outgoing: Vector,
+    join: LineJoin,
+    push: &mut impl FnMut(Vector, Vector),
+) {
+    let incoming_normal = normal(incoming);
+    let outgoing_normal = normal(outgoing);
+    let miter = normalize(incoming_normal + outgoing_normal);
+    let cos_half_angle = dot(miter, outgoing_normal);
+    let miter_scale = if cos_half_angle > f32::EPSILON {
+        1.0 / cos_half_angle
+    } else {
+        f32::INFINITY
+    };
+    let angle = dot(incoming, outgoing).clamp(-1.0, 1.0).acos();
+
+    let mitered = match join {
+        LineJoin::Miter => miter_scale <= MITER_LIMIT,
+        LineJoin::Round => angle < ROUND_JOIN_MIN_ANGLE,
+    };
+    if mitered {
+        push(miter * miter_scale, miter * -miter_scale);
+        return;
+    }
+
+    // The inner edges still meet in a (limited) miter, while the outer edges are beveled or
+    // rounded. Which side is outer depends on the direction of the bend.
+    let inner_sign = if cross(incoming, outgoing) > 0.0 {
+        1.0
+    } else {
+        -1.0
+    };
+    let inner = miter * (inner_sign * miter_scale.min(MITER_LIMIT));
+    let from = incoming_normal * -inner_sign;
+    let to = outgoing_normal * -inner_sign;
+
+    let steps = match join {
+        LineJoin::Miter => 1,
+        LineJoin::Round => (angle / ROUND_SEGMENT_ANGLE).ceil().max(1.0) as usize,
+    };
+    for step in 0..=steps {
+        let outer = rotate_towards(from, to, angle, step as f32 / steps as f32);
+        if inner_sign > 0.0 {
+            push(inner, outer);
+        } else {
+            push(outer, inner);
+        }
+    }
+}
+
+/// Generates a half circle at the end of a stroke, pointing backwards (`sign` -1) or forwards
+/// (`sign` 1) from the given section.
+fn round_cap(stroke: &mut Stroke, section: Section, sign: f32, core: f32, alpha: f32) {
+    let direction = Vector::new(section.left.y, -section.left.x) * sign;
+    let steps = (std::f32::consts::PI / ROUND_SEGMENT_ANGLE).ceil() as usize;
+
+    let center = stroke.push(section.center, section.distance, alpha);
+    let mut previous: Option<(u32, u32)> = None;
+    for step in 0..=steps {
+        let t = step as f32 / steps as f32;
+        let angle = t * std::f32::consts::PI;
+        // Sweep from one edge of the stroke around the end to the other edge
+        let offset = section.left * angle.cos() + direction * angle.sin();
+        let inner = stroke.push(section.center + offset * core, section.distance, alpha);
+        let outer = stroke.push(
+            section.center + offset * (core + FEATHER),
+            section.distance,
+            0.0,
+        );
+
+        if let Some((previous_inner, previous_outer)) = previous {
+            stroke.indices.extend([
+                center,
+                previous_inner,
+                inner,
+                previous_inner,
+                previous_outer,
+                outer,
+                previous_inner,
+                outer,
+                inner,
+            ]);
+        }
+        previous = Some((inner, outer));
+    }
+}
+
+/// Rotates the unit vector `from` towards `to`, which are `angle` radians apart, by the given
+/// fraction of that angle.
+fn rotate_towards(from: Vector, to: Vector, angle: f32, t: f32) -> Vector {
+    if angle < f32::EPSILON {
+        return from;
+    }
+    let sin = angle.sin();
+    if sin.abs() < f32::EPSILON {
+        // Opposite vectors; rotate around the perpendicular of `from` in either direction
+        let perpendicular = Vector::new(-from.y, from.x);
+        let rotation = angle * t;
+        return from * rotation.cos() + perpendicular * rotation.sin();
+    }
+
+    // Spherical linear interpolation
+    from * (((1.0 - t) * angle).sin() / sin) + to * ((t * angle).sin() / sin)
+}
+
+fn normal(direction: Vector) -> Vector {
+    Vector::new(-direction.y, direction.x)
+}
+
+fn normalize(vector: Vector) -> Vector {
+    let length = length(vector);
+    if length == 0.0 {
+        Vector::new(0.0, 0.0)
+    } else {
+        vector * (1.0 / length)
+    }
+}
+
+fn length(vector: Vector) -> f32 {
+    (vector.x * vector.x + vector.y * vector.y).sqrt()
+}
+
+fn dot(a: Vector, b: Vector) -> f32 {
+    a.x * b.x + a.y * b.y
+}
+
+fn cross(a: Vector, b: Vector) -> f32 {
+    a.x * b.y - a.y * b.x
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Sums up the areas of all triangles of a stroke.
+    fn area(stroke: &Stroke) -> f32 {
+        stroke
+            .indices
+            .chunks(3)
+            .map(|triangle| {
+                let [a, b, c] = [0, 1, 2].map(|i| stroke.vertices[triangle[i] as usize].position);
+                cross(b - a, c - a).abs() / 2.0
+            })
+            .sum()
+    }
+
+    #[test]
+    fn degenerate_strokes_are_empty() {
+        let points = [Vector::new(1.0, 1.0), Vector::new(1.0, 1.0)];
+        let stroke = stroke(&points, 2.0, 0.0, LineJoin::Round, LineCap::Round);
+
+        assert!(stroke.vertices.is_empty());
+        assert!(stroke.indices.is_empty());
+    }
+
+    #[test]
+    fn straight_stroke_covers_its_width_and_fringe() {
+        let points = [Vector::new(0.0, 0.0), Vector::new(10.0, 0.0)];
+        let stroke = stroke(&points, 2.0, 5.0, LineJoin::Round, LineCap::Butt);
+
+        // The core is narrowed by half the fringe on each side, the fringe adds a full one
+        let width = 2.0 * (2.0 - FEATHER / 2.0 + FEATHER);
+        assert!((area(&stroke) - 10.0 * width).abs() < 1e-3);
+        assert_eq!(
+            stroke.vertices.first().map(|vertex| vertex.distance),
+            Some(5.0)
+        );
+        assert_eq!(
+            stroke.vertices.last().map(|vertex| vertex.distance),
+            Some(15.0)
+        );
+    }
+
+    #[test]
+    fn round_joins_add_sections_at_sharp_bends() {
+        let points = [
+            Vector::new(0.0, 0.0),
+            Vector::new(10.0, 0.0),
+            Vector::new(0.0, 1.0),
+        ];
+        let mitered = stroke(&points, 2.0, 0.0, LineJoin::Miter, LineCap::Butt);
+        let rounded = stroke(&points, 2.0, 0.0, LineJoin::Round, LineCap::Butt);
+
+        assert!(rounded.vertices.len() > mitered.vertices.len());
+    }
+
+    #[test]
+    fn fill_covers_every_edge_once() {
+        let square = [
+            Vector::new(0.0, 0.0),
+            Vector::new(10.0, 0.0),
+            Vector::new(10.0, 10.0),
+            Vector::new(0.0, 10.0),
+        ];
+        let fill = fill(&square, 0.0);
+
+        // The polygon itself plus a fringe extending outwards by `FEATHER`
+        let outer = 10.0 + 2.0 * FEATHER;
+        assert!((area(&fill) - outer * outer).abs() < 1e-3);
+    }
+
+    #[test]
+    fn fill_fringe_lies_outside_the_polygon() {
+        // Both windings, as the fringe has to find the outside either way
+        let triangle = [
+            Vector::new(0.0, 0.0),
+            Vector::new(10.0, 0.0),
+            Vector::new(5.0, 8.0),
+        ];
+        let reversed = [triangle[2], triangle[1], triangle[0]];
+
+        for polygon in [triangle, reversed] {
+            let fill = fill(&polygon, 0.0);
+            let fringe = &fill.vertices[1 + polygon.len()..];
+            assert_eq!(fringe.len(), polygon.len());
+
+            for vertex in fringe {
+                assert_eq!(vertex.alpha, 0.0);
+                // A point is outside a convex polygon if it lies beyond any of its edges
+                let outside = (0..polygon.len()).any(|i| {
+                    let edge = polygon[(i + 1) % polygon.len()] - polygon[i];
+                    let side = cross(edge, vertex.position - polygon[i]);
+                    let inside = cross(edge, polygon[(i + 2) % polygon.len()] - polygon[i]);
+                    side * inside < 0.0
+                });
+                assert!(outside, "{:?} lies inside the polygon", vertex.position);
+            }
+        }
+    }
+}
@@ -1,8 +1,20 @@
-use iced::{Color, Theme};
+use iced::{Background, Color, Theme};
 
 #[derive(Debug, Clone, Copy)]
 pub struct Appearance {
     pub color: Option<Color>,
+    /// Draws the connection dashed instead of solid. The pattern is given in pixels, such that it
+    /// looks the same at every zoom level.
+    pub dash_pattern: Option<DashPattern>,
+    /// The background of the pill behind the connection's label.
+    pub label_background: Background,
+}
+
+/// A repeating pattern of dashes and gaps along a connection, in pixels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DashPattern {
+    pub dash: f32,
+    pub gap: f32,
 }
 
 pub trait StyleSheet {
@@ -14,6 +26,8 @@ pub trait StyleSheet {
 pub enum Node {
     #[default]
     Default,
+    Dashed,
+    Dotted,
     Custom(Box<dyn StyleSheet<Style = Theme>>),
 }
 
@@ -26,6 +40,24 @@ impl StyleSheet for Theme {
         match style {
             Node::Default => Appearance {
                 color: Some(palette.primary.base.color),
+                dash_pattern: None,
+                label_background: palette.background.weak.color.into(),
+            },
+            Node::Dashed => Appearance {
+                color: Some(palette.primary.base.color),
+                dash_pattern: Some(DashPattern {
+                    dash: 6.0,
+                    gap: 4.0,
+                }),
+                label_background: palette.background.weak.color.into(),
+            },
+            Node::Dotted => Appearance {
+                color: Some(palette.primary.base.color),
+                dash_pattern: Some(DashPattern {
+                    dash: 2.0,
+                    gap: 3.0,
+                }),
+                label_background: palette.background.weak.color.into(),
             },
             Node::Custom(custom) => custom.appearance(self),
         }
@@ -10,6 +10,10 @@ pub struct Appearance {
     pub minor_guidelines_spacing: Option<f32>,
     pub mid_guidelines_spacing: Option<f32>,
     pub major_guidelines_spacing: Option<f32>,
+    pub compatible_socket_color: Option<Color>,
+    pub incompatible_socket_color: Option<Color>,
+    pub cycle_socket_color: Option<Color>,
+    pub waypoint_color: Option<Color>,
 }
 
 pub trait StyleSheet {
@@ -60,6 +64,13 @@ impl StyleSheet for Theme {
                 minor_guidelines_spacing: Some(10.0),
                 mid_guidelines_spacing: Some(50.0),
                 major_guidelines_spacing: Some(100.0),
+                compatible_socket_color: Some(palette.success.base.color),
+                incompatible_socket_color: Some(Color {
+                    a: 0.7,
+                    ..palette.background.base.color
+                }),
+                cycle_socket_color: Some(palette.danger.base.color),
+                waypoint_color: Some(palette.primary.base.color),
             },
             GraphContainer::Custom(custom) => custom.appearance(self),
         }
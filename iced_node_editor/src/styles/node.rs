@@ -7,6 +7,8 @@ pub struct Appearance {
     pub border_radius: f32,
     pub border_width: f32,
     pub border_color: Color,
+    /// The background of the bar showing the header of a node, if it has one.
+    pub header_background: Option<Background>,
 }
 
 impl Default for Appearance {
@@ -17,6 +19,7 @@ impl Default for Appearance {
             border_radius: 0.0,
             border_width: 0.0,
             border_color: Color::TRANSPARENT,
+            header_background: None,
         }
     }
 }
@@ -46,6 +49,7 @@ impl StyleSheet for Theme {
                 border_radius: 5.0,
                 border_width: 1.0,
                 text_color: Some(palette.primary.base.color),
+                header_background: Some(Background::Color(palette.background.strong.color)),
             },
             Node::Custom(custom) => custom.appearance(self),
         }